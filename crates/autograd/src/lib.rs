@@ -0,0 +1,91 @@
+use std::cell::RefCell;
+
+use float::Float;
+use ndarray::Array2;
+
+pub use var::Var;
+
+// returns gradients of the parents from the gradient of the node
+type BackwardFn<T> = Box<dyn Fn(&Array2<T>) -> Vec<Array2<T>>>;
+
+struct Node<T> {
+    value: Array2<T>,
+    parents: Vec<usize>,
+    backward: Option<BackwardFn<T>>,
+}
+
+// Records every operation on its `Var`s, reverse-mode gradients are computed by `Var::backward`.
+// Nodes are only appended, so their order is a topological order of the graph.
+// Values and gradients are `T`, e.g. f64 for gradients of f64 models.
+pub struct Tape<T = f32> {
+    nodes: RefCell<Vec<Node<T>>>,
+}
+
+impl<T: Float> Default for Tape<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Float> Tape<T> {
+    pub fn new() -> Self {
+        Tape { nodes: RefCell::new(Vec::new()) }
+    }
+
+    // Leaf of the graph, e.g. an input or a parameter
+    pub fn var(&self, value: Array2<T>) -> Var<'_, T> {
+        self.push(value, Vec::new(), None)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn push(&self, value: Array2<T>, parents: Vec<usize>, backward: Option<BackwardFn<T>>) -> Var<'_, T> {
+        let mut nodes = self.nodes.borrow_mut();
+        nodes.push(Node { value, parents, backward });
+        Var::new(self, nodes.len() - 1)
+    }
+
+    fn value(&self, idx: usize) -> Array2<T> {
+        self.nodes.borrow()[idx].value.clone()
+    }
+
+    fn backward(&self, output: usize, seed: Array2<T>) -> Gradients<T> {
+        let nodes = self.nodes.borrow();
+        let mut grads: Vec<Option<Array2<T>>> = vec![None; nodes.len()];
+        grads[output] = Some(seed);
+
+        for idx in (0..=output).rev() {
+            let (Some(grad), Some(backward)) = (grads[idx].as_ref(), nodes[idx].backward.as_ref()) else {
+                continue;
+            };
+            let parent_grads = backward(grad);
+            for (&parent, parent_grad) in nodes[idx].parents.iter().zip(parent_grads) {
+                match grads[parent].as_mut() {
+                    Some(acc) => *acc += &parent_grad,
+                    None => grads[parent] = Some(parent_grad),
+                }
+            }
+        }
+        Gradients { grads }
+    }
+}
+
+// Gradients of one output with respect to every node recorded before it
+pub struct Gradients<T = f32> {
+    grads: Vec<Option<Array2<T>>>,
+}
+
+impl<T: Float> Gradients<T> {
+    // None if the output does not depend on `var`
+    pub fn wrt(&self, var: Var<T>) -> Option<&Array2<T>> {
+        self.grads.get(var.idx()).and_then(|g| g.as_ref())
+    }
+}
+
+pub mod var;
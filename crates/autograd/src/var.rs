@@ -0,0 +1,275 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::ptr;
+
+use float::Float;
+use ndarray::{s, Array2, Axis};
+
+use super::{BackwardFn, Gradients, Tape};
+
+// Handle to a value recorded on a `Tape`. Element-wise operations broadcast like ndarray,
+// e.g. a (batch, n) matrix plus a (1, n) bias row.
+pub struct Var<'t, T = f32> {
+    tape: &'t Tape<T>,
+    idx: usize,
+}
+
+// a handle, copied whatever `T` is
+impl<T> Clone for Var<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Var<'_, T> {}
+
+impl<'t, T: Float> Var<'t, T> {
+    pub(crate) fn new(tape: &'t Tape<T>, idx: usize) -> Self {
+        Var { tape, idx }
+    }
+
+    pub(crate) fn idx(&self) -> usize {
+        self.idx
+    }
+
+    pub fn value(&self) -> Array2<T> {
+        self.tape.value(self.idx)
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        self.tape.nodes.borrow()[self.idx].value.dim()
+    }
+
+    // Gradients of this 1x1 value, e.g. a loss
+    pub fn backward(&self) -> Gradients<T> {
+        assert_eq!(self.shape(), (1, 1), "backward needs a scalar, use backward_with for other shapes");
+        self.tape.backward(self.idx, Array2::ones((1, 1)))
+    }
+
+    // Gradients of sum(self * seed), `seed` is the gradient flowing into this value
+    pub fn backward_with(&self, seed: &Array2<T>) -> Gradients<T> {
+        assert_eq!(self.shape(), seed.dim(), "seed must have the shape of the value");
+        self.tape.backward(self.idx, seed.clone())
+    }
+
+    fn unary(self, value: Array2<T>, backward: impl Fn(&Array2<T>) -> Array2<T> + 'static) -> Var<'t, T> {
+        let backward: BackwardFn<T> = Box::new(move |grad| vec![backward(grad)]);
+        self.tape.push(value, vec![self.idx], Some(backward))
+    }
+
+    fn binary(
+        self,
+        other: Var<'t, T>,
+        value: Array2<T>,
+        backward: impl Fn(&Array2<T>) -> (Array2<T>, Array2<T>) + 'static,
+    ) -> Var<'t, T> {
+        assert!(ptr::eq(self.tape, other.tape), "Vars must be on the same tape");
+        let (lhs, rhs) = (self.shape(), other.shape());
+        let backward: BackwardFn<T> = Box::new(move |grad| {
+            let (a, b) = backward(grad);
+            vec![unbroadcast(a, lhs), unbroadcast(b, rhs)]
+        });
+        self.tape.push(value, vec![self.idx, other.idx], Some(backward))
+    }
+
+    pub fn matmul(self, other: Var<'t, T>) -> Var<'t, T> {
+        let (a, b) = (self.value(), other.value());
+        let value = a.dot(&b);
+        self.binary(other, value, move |grad| (grad.dot(&b.t()), a.t().dot(grad)))
+    }
+
+    pub fn t(self) -> Var<'t, T> {
+        let value = self.value().reversed_axes().as_standard_layout().to_owned();
+        self.unary(value, |grad| grad.t().to_owned())
+    }
+
+    pub fn scale(self, factor: T) -> Var<'t, T> {
+        let value = self.value().mapv(|v| v * factor);
+        self.unary(value, move |grad| grad.mapv(|g| g * factor))
+    }
+
+    pub fn add_scalar(self, scalar: T) -> Var<'t, T> {
+        let value = self.value().mapv(|v| v + scalar);
+        self.unary(value, |grad| grad.clone())
+    }
+
+    pub fn powi(self, n: i32) -> Var<'t, T> {
+        let x = self.value();
+        let value = x.mapv(|v| v.powi(n));
+        let n_t = T::from_f64(n as f64);
+        self.unary(value, move |grad| grad * &x.mapv(|v| n_t * v.powi(n - 1)))
+    }
+
+    pub fn exp(self) -> Var<'t, T> {
+        let value = self.value().mapv(T::exp);
+        let y = value.clone();
+        self.unary(value, move |grad| grad * &y)
+    }
+
+    pub fn ln(self) -> Var<'t, T> {
+        let x = self.value();
+        let value = x.mapv(T::ln);
+        self.unary(value, move |grad| grad / &x)
+    }
+
+    pub fn tanh(self) -> Var<'t, T> {
+        let value = self.value().mapv(T::tanh);
+        let y = value.clone();
+        self.unary(value, move |grad| grad * &y.mapv(|v| T::one() - v * v))
+    }
+
+    pub fn sigmoid(self) -> Var<'t, T> {
+        let value = self.value().mapv(|v| T::one() / (T::one() + (-v).exp()));
+        let y = value.clone();
+        self.unary(value, move |grad| grad * &y.mapv(|v| v * (T::one() - v)))
+    }
+
+    pub fn relu(self) -> Var<'t, T> {
+        let x = self.value();
+        let value = x.mapv(|v| v.max(T::zero()));
+        self.unary(value, move |grad| grad * &x.mapv(|v| if v > T::zero() { T::one() } else { T::zero() }))
+    }
+
+    // sum of all values as 1x1
+    pub fn sum(self) -> Var<'t, T> {
+        let shape = self.shape();
+        let value = Array2::from_elem((1, 1), self.value().sum());
+        self.unary(value, move |grad| Array2::from_elem(shape, grad[[0, 0]]))
+    }
+
+    pub fn mean(self) -> Var<'t, T> {
+        let (rows, cols) = self.shape();
+        self.sum().scale(T::one() / T::from_f64((rows * cols) as f64))
+    }
+
+    // sum along `axis`, keeping it with length 1
+    pub fn sum_axis(self, axis: Axis) -> Var<'t, T> {
+        let shape = self.shape();
+        let value = self.value().sum_axis(axis).insert_axis(axis);
+        self.unary(value, move |grad| grad.broadcast(shape).unwrap().to_owned())
+    }
+
+    // row-wise softmax
+    pub fn softmax(self) -> Var<'t, T> {
+        let value = softmax_rows(&self.value());
+        let y = value.clone();
+        self.unary(value, move |grad| {
+            let dot = (grad * &y).sum_axis(Axis(1)).insert_axis(Axis(1));
+            &y * &(grad - &dot)
+        })
+    }
+
+    // row-wise log of softmax, stable for large logits
+    pub fn log_softmax(self) -> Var<'t, T> {
+        let x = self.value();
+        let probs = softmax_rows(&x);
+        let value = probs.mapv(|p| p.max(T::min_positive_value()).ln());
+        self.unary(value, move |grad| grad - &(&probs * &grad.sum_axis(Axis(1)).insert_axis(Axis(1))))
+    }
+
+    // columns start..end, e.g. one gate of packed LSTM weights
+    pub fn slice_cols(self, start: usize, end: usize) -> Var<'t, T> {
+        let shape = self.shape();
+        let value = self.value().slice(s![.., start..end]).to_owned();
+        self.unary(value, move |grad| {
+            let mut full = Array2::zeros(shape);
+            full.slice_mut(s![.., start..end]).assign(grad);
+            full
+        })
+    }
+
+    // side by side, all parts need the same number of rows
+    pub fn concat_cols(parts: &[Var<'t, T>]) -> Var<'t, T> {
+        let tape = parts.first().expect("nothing to concatenate").tape;
+        let values: Vec<Array2<T>> = parts.iter().map(|p| p.value()).collect();
+        let views: Vec<_> = values.iter().map(|v| v.view()).collect();
+        let value = ndarray::concatenate(Axis(1), &views).expect("parts must have the same number of rows");
+        let widths: Vec<usize> = values.iter().map(|v| v.ncols()).collect();
+        let backward: BackwardFn<T> = Box::new(move |grad| {
+            let mut start = 0;
+            widths
+                .iter()
+                .map(|&w| {
+                    start += w;
+                    grad.slice(s![.., start - w..start]).to_owned()
+                })
+                .collect()
+        });
+        tape.push(value, parts.iter().map(|p| p.idx).collect(), Some(backward))
+    }
+}
+
+impl<'t, T: Float> Add for Var<'t, T> {
+    type Output = Var<'t, T>;
+
+    fn add(self, other: Var<'t, T>) -> Var<'t, T> {
+        let value = &self.value() + &other.value();
+        self.binary(other, value, |grad| (grad.clone(), grad.clone()))
+    }
+}
+
+impl<'t, T: Float> Sub for Var<'t, T> {
+    type Output = Var<'t, T>;
+
+    fn sub(self, other: Var<'t, T>) -> Var<'t, T> {
+        let value = &self.value() - &other.value();
+        self.binary(other, value, |grad| (grad.clone(), grad.mapv(|g| -g)))
+    }
+}
+
+impl<'t, T: Float> Mul for Var<'t, T> {
+    type Output = Var<'t, T>;
+
+    // element-wise
+    fn mul(self, other: Var<'t, T>) -> Var<'t, T> {
+        let (a, b) = (self.value(), other.value());
+        let value = &a * &b;
+        self.binary(other, value, move |grad| (grad * &b, grad * &a))
+    }
+}
+
+impl<'t, T: Float> Div for Var<'t, T> {
+    type Output = Var<'t, T>;
+
+    fn div(self, other: Var<'t, T>) -> Var<'t, T> {
+        let (a, b) = (self.value(), other.value());
+        let value = &a / &b;
+        self.binary(other, value, move |grad| quotient_grads(grad, &a, &b))
+    }
+}
+
+impl<'t, T: Float> Neg for Var<'t, T> {
+    type Output = Var<'t, T>;
+
+    fn neg(self) -> Var<'t, T> {
+        self.scale(-T::one())
+    }
+}
+
+// d(a / b)/da and d(a / b)/db
+fn quotient_grads<T: Float>(grad: &Array2<T>, a: &Array2<T>, b: &Array2<T>) -> (Array2<T>, Array2<T>) {
+    (grad / b, -(grad * a) / &(b * b))
+}
+
+// Sums a broadcast gradient back to the operand's shape
+fn unbroadcast<T: Float>(grad: Array2<T>, shape: (usize, usize)) -> Array2<T> {
+    let mut grad = grad;
+    // the gradient has the full shape, so the operand could only have been stretched along axes of length 1
+    if shape.0 == 1 && grad.nrows() != 1 {
+        grad = grad.sum_axis(Axis(0)).insert_axis(Axis(0));
+    }
+    if shape.1 == 1 && grad.ncols() != 1 {
+        grad = grad.sum_axis(Axis(1)).insert_axis(Axis(1));
+    }
+    grad
+}
+
+fn softmax_rows<T: Float>(x: &Array2<T>) -> Array2<T> {
+    let mut out = x.clone();
+    for mut row in out.axis_iter_mut(Axis(0)) {
+        let max = row.iter().cloned().fold(T::neg_infinity(), T::max);
+        row.mapv_inplace(|v| (v - max).exp());
+        let sum = row.sum();
+        row.mapv_inplace(|v| v / sum);
+    }
+    out
+}
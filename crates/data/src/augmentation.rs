@@ -0,0 +1,202 @@
+use std::error::Error;
+
+use ndarray::{Array2, ArrayViewMut1};
+use ndarray_rand::rand::{Rng, SeedableRng, rngs::StdRng};
+use ndarray_rand::rand_distr::{Distribution, Normal};
+use super::{AUGMENT_STREAM, derive_seed};
+
+#[derive(Clone, Debug)]
+pub enum Augmentation {
+    // shift by up to `max_shift` pixels in each direction
+    Translate { max_shift: f32 },
+    // rotate around the center by up to `max_degrees` either way
+    Rotate { max_degrees: f32 },
+    // zoom around the center by a factor in [min, max]
+    Scale { min: f32, max: f32 },
+    // random displacement field smoothed with a gaussian of `sigma`, scaled by `alpha` pixels
+    Elastic { alpha: f32, sigma: f32 },
+    GaussianNoise { std: f32 },
+    // with `probability` zero a rectangle covering [min_area, max_area] of the image
+    RandomErasing { probability: f32, min_area: f32, max_area: f32 },
+}
+
+impl Augmentation {
+    // Ranges must be ordered and sigma positive, otherwise sampling panics or blurring gives NaN
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        let valid = match *self {
+            Augmentation::Translate { max_shift } => max_shift >= 0.0 && max_shift.is_finite(),
+            Augmentation::Rotate { max_degrees } => max_degrees >= 0.0 && max_degrees.is_finite(),
+            Augmentation::Scale { min, max } => min > 0.0 && min <= max && max.is_finite(),
+            Augmentation::Elastic { alpha, sigma } => alpha.is_finite() && sigma > 0.0 && sigma.is_finite(),
+            Augmentation::GaussianNoise { std } => std >= 0.0 && std.is_finite(),
+            Augmentation::RandomErasing { probability, min_area, max_area } => {
+                (0.0..=1.0).contains(&probability) && min_area > 0.0 && min_area <= max_area && max_area <= 1.0
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(format!("Invalid augmentation parameters: {:?}", self).into())
+        }
+    }
+}
+
+// Applies augmentations in order to images flattened row-major into rows of shape (height * width)
+#[derive(Clone)]
+pub struct Augmenter {
+    pub height: usize,
+    pub width: usize,
+    pub augmentations: Vec<Augmentation>,
+    pub seed: u64,
+    rng: StdRng,
+}
+
+impl Augmenter {
+    pub fn new(height: usize, width: usize, augmentations: Vec<Augmentation>, seed: u64) -> Result<Self, Box<dyn Error>> {
+        if height == 0 || width == 0 {
+            return Err("Images must have at least one pixel".into());
+        }
+        for aug in augmentations.iter() {
+            aug.validate()?;
+        }
+        Ok(Augmenter {
+            height,
+            width,
+            augmentations,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        })
+    }
+
+    // Same augmentations with a random stream derived from seed and epoch, `seed` stays the base seed
+    pub fn for_epoch(&self, epoch: u64) -> Self {
+        Augmenter {
+            rng: StdRng::seed_from_u64(derive_seed(self.seed, AUGMENT_STREAM, epoch)),
+            ..self.clone()
+        }
+    }
+
+    // returns augmented copy of `x` with shape (batch size, height * width)
+    pub fn apply(&mut self, x: &Array2<f32>) -> Array2<f32> {
+        assert_eq!(x.ncols(), self.height * self.width, "Rows must be images of height * width pixels");
+        let mut out = x.clone();
+        for mut image in out.rows_mut() {
+            for aug in self.augmentations.iter() {
+                apply_one(aug, &mut image, self.height, self.width, &mut self.rng);
+            }
+        }
+        out
+    }
+}
+
+fn apply_one(aug: &Augmentation, image: &mut ArrayViewMut1<f32>, h: usize, w: usize, rng: &mut StdRng) {
+    let (cy, cx) = ((h as f32 - 1.0) / 2.0, (w as f32 - 1.0) / 2.0);
+
+    match *aug {
+        Augmentation::Translate { max_shift } => {
+            let dy = rng.gen_range(-max_shift..=max_shift);
+            let dx = rng.gen_range(-max_shift..=max_shift);
+            warp(image, h, w, |r, c| (r - dy, c - dx));
+        }
+        Augmentation::Rotate { max_degrees } => {
+            let theta = rng.gen_range(-max_degrees..=max_degrees).to_radians();
+            let (sin, cos) = theta.sin_cos();
+            // inverse rotation maps output pixels back to the source
+            warp(image, h, w, |r, c| {
+                let (y, x) = (r - cy, c - cx);
+                (cy + cos * y - sin * x, cx + sin * y + cos * x)
+            });
+        }
+        Augmentation::Scale { min, max } => {
+            let factor = rng.gen_range(min..=max);
+            warp(image, h, w, |r, c| (cy + (r - cy) / factor, cx + (c - cx) / factor));
+        }
+        Augmentation::Elastic { alpha, sigma } => {
+            let field = |rng: &mut StdRng| {
+                let noise: Vec<f32> = (0..h * w).map(|_| rng.gen_range(-1.0..=1.0)).collect();
+                gaussian_blur(&noise, h, w, sigma).into_iter().map(|d| d * alpha).collect::<Vec<f32>>()
+            };
+            let (dy, dx) = (field(rng), field(rng));
+            warp(image, h, w, |r, c| {
+                let idx = r as usize * w + c as usize;
+                (r + dy[idx], c + dx[idx])
+            });
+        }
+        Augmentation::GaussianNoise { std } => {
+            let noise = Normal::new(0.0, std).unwrap();
+            image.mapv_inplace(|p| p + noise.sample(rng));
+        }
+        Augmentation::RandomErasing { probability, min_area, max_area } => {
+            if rng.r#gen::<f32>() >= probability {
+                return;
+            }
+            let area = rng.gen_range(min_area..=max_area) * (h * w) as f32;
+            let aspect: f32 = rng.gen_range(0.3f32..=3.3);
+            let eh = ((area * aspect).sqrt().round() as usize).clamp(1, h);
+            let ew = ((area / aspect).sqrt().round() as usize).clamp(1, w);
+            let top = rng.gen_range(0..=h - eh);
+            let left = rng.gen_range(0..=w - ew);
+            for r in top..top + eh {
+                for c in left..left + ew {
+                    image[r * w + c] = 0.0;
+                }
+            }
+        }
+    }
+}
+
+// Resamples the image, `source` maps output (row, col) to the source position.
+// Positions outside the image read as 0.
+fn warp<F: Fn(f32, f32) -> (f32, f32)>(image: &mut ArrayViewMut1<f32>, h: usize, w: usize, source: F) {
+    let src = image.to_vec();
+    for r in 0..h {
+        for c in 0..w {
+            let (sr, sc) = source(r as f32, c as f32);
+            image[r * w + c] = bilinear(&src, h, w, sr, sc);
+        }
+    }
+}
+
+fn bilinear(src: &[f32], h: usize, w: usize, r: f32, c: f32) -> f32 {
+    let (r0, c0) = (r.floor(), c.floor());
+    let (fr, fc) = (r - r0, c - c0);
+    let pixel = |r: f32, c: f32| {
+        if r < 0.0 || c < 0.0 || r >= h as f32 || c >= w as f32 {
+            0.0
+        } else {
+            src[r as usize * w + c as usize]
+        }
+    };
+    pixel(r0, c0) * (1.0 - fr) * (1.0 - fc)
+        + pixel(r0, c0 + 1.0) * (1.0 - fr) * fc
+        + pixel(r0 + 1.0, c0) * fr * (1.0 - fc)
+        + pixel(r0 + 1.0, c0 + 1.0) * fr * fc
+}
+
+// Separable gaussian blur with edges clamped
+fn gaussian_blur(values: &[f32], h: usize, w: usize, sigma: f32) -> Vec<f32> {
+    let radius = (3.0 * sigma).ceil().max(1.0) as isize;
+    let kernel: Vec<f32> = (-radius..=radius).map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp()).collect();
+    let norm: f32 = kernel.iter().sum();
+
+    let blur = |input: &[f32], along_rows: bool| {
+        let mut output = vec![0.0; h * w];
+        for r in 0..h {
+            for c in 0..w {
+                let mut acc = 0.0;
+                for (k, weight) in kernel.iter().enumerate() {
+                    let offset = k as isize - radius;
+                    let (rr, cc) = if along_rows {
+                        (r as isize, (c as isize + offset).clamp(0, w as isize - 1))
+                    } else {
+                        ((r as isize + offset).clamp(0, h as isize - 1), c as isize)
+                    };
+                    acc += weight * input[rr as usize * w + cc as usize];
+                }
+                output[r * w + c] = acc / norm;
+            }
+        }
+        output
+    };
+    blur(&blur(values, true), false)
+}
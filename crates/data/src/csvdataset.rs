@@ -0,0 +1,44 @@
+use std::error::Error;
+
+use polars::prelude::*;
+use super::{ArrayDataset, Batch, Dataset};
+
+// CSV file with a header, loaded into memory
+pub struct CsvDataset {
+    pub data: ArrayDataset,
+}
+
+impl CsvDataset {
+    // `feature_cols` of None takes every column except `label_col`
+    pub fn new(path: &str, label_col: &str, feature_cols: Option<&[&str]>) -> Result<Self, Box<dyn Error>> {
+        let lf = LazyCsvReader::new(path)
+            .with_has_header(true)
+            .finish()?;
+
+        let labels = lf
+            .clone()
+            .select([col(label_col)])
+            .collect()?;
+
+        let features = match feature_cols {
+            Some(cols) => lf.select(cols.iter().map(|c| col(*c)).collect::<Vec<_>>()),
+            None => lf.drop([col(label_col)]),
+        }
+        .collect()?;
+
+        let features = features.to_ndarray::<Float32Type>(IndexOrder::C)?;
+        let labels = labels.to_ndarray::<Float32Type>(IndexOrder::C)?;
+
+        Ok(CsvDataset { data: ArrayDataset::new(features, labels) })
+    }
+}
+
+impl Dataset for CsvDataset {
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    fn get_batch(&self, indices: &[usize]) -> Batch {
+        self.data.get_batch(indices)
+    }
+}
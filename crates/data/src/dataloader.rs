@@ -0,0 +1,121 @@
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use ndarray_rand::rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+use super::{Augmenter, Batch, Dataset, SHUFFLE_STREAM, derive_seed};
+
+pub struct DataLoader<D: Dataset> {
+    pub dataset: Arc<D>,
+    pub batch_size: usize,
+    // seed for shuffling, None keeps the dataset order
+    pub shuffle: Option<u64>,
+    // skip the last batch if it is smaller than `batch_size`
+    pub drop_last: bool,
+    // number of batches prepared ahead on a background thread, 0 loads batches on the caller's thread
+    pub prefetch: usize,
+    // augments the features of every batch, reseeded every epoch
+    pub augmenter: Option<Augmenter>,
+    pub epoch: u64,
+}
+
+impl<D: Dataset + 'static> DataLoader<D> {
+    pub fn new(dataset: D, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "Batch size must be positive");
+        DataLoader {
+            dataset: Arc::new(dataset),
+            batch_size,
+            shuffle: None,
+            drop_last: false,
+            prefetch: 0,
+            augmenter: None,
+            epoch: 0,
+        }
+    }
+
+    pub fn num_batches(&self) -> usize {
+        let len = self.dataset.len();
+        if self.drop_last {
+            len / self.batch_size
+        } else {
+            len.div_ceil(self.batch_size)
+        }
+    }
+
+    // Batches of one epoch. With shuffling every epoch gets a new order derived from seed and epoch.
+    pub fn iter(&mut self) -> Batches<D> {
+        let mut order: Vec<usize> = (0..self.dataset.len()).collect();
+        if let Some(seed) = self.shuffle {
+            let mut rng = StdRng::seed_from_u64(derive_seed(seed, SHUFFLE_STREAM, self.epoch));
+            order.shuffle(&mut rng);
+        }
+        let batches = BatchIter {
+            dataset: Arc::clone(&self.dataset),
+            order,
+            batch_size: self.batch_size,
+            drop_last: self.drop_last,
+            augmenter: self.augmenter.as_ref().map(|a| a.for_epoch(self.epoch)),
+            pos: 0,
+        };
+        self.epoch += 1;
+
+        if self.prefetch == 0 {
+            return Batches::Sequential(batches);
+        }
+
+        let (sender, receiver) = mpsc::sync_channel(self.prefetch);
+        thread::spawn(move || {
+            for batch in batches {
+                // receiver dropped, nobody needs the rest of the epoch
+                if sender.send(batch).is_err() {
+                    break;
+                }
+            }
+        });
+        Batches::Prefetch(receiver)
+    }
+}
+
+pub struct BatchIter<D: Dataset> {
+    dataset: Arc<D>,
+    order: Vec<usize>,
+    batch_size: usize,
+    drop_last: bool,
+    augmenter: Option<Augmenter>,
+    pos: usize,
+}
+
+impl<D: Dataset> Iterator for BatchIter<D> {
+    type Item = Batch;
+
+    fn next(&mut self) -> Option<Batch> {
+        let remaining = self.order.len() - self.pos;
+        if remaining == 0 || (self.drop_last && remaining < self.batch_size) {
+            return None;
+        }
+        let end = self.pos + remaining.min(self.batch_size);
+        let (mut features, labels) = self.dataset.get_batch(&self.order[self.pos..end]);
+        if let Some(augmenter) = self.augmenter.as_mut() {
+            features = augmenter.apply(&features);
+        }
+        self.pos = end;
+        Some((features, labels))
+    }
+}
+
+#[allow(clippy::large_enum_variant)]
+pub enum Batches<D: Dataset> {
+    Sequential(BatchIter<D>),
+    Prefetch(Receiver<Batch>),
+}
+
+impl<D: Dataset> Iterator for Batches<D> {
+    type Item = Batch;
+
+    fn next(&mut self) -> Option<Batch> {
+        match self {
+            Batches::Sequential(batches) => batches.next(),
+            Batches::Prefetch(receiver) => receiver.recv().ok(),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use ndarray::{Array2, Axis};
+use super::{Batch, Dataset};
+
+// Dataset already held in memory as ndarray arrays
+pub struct ArrayDataset {
+    pub features: Array2<f32>, // shape (rows, number of features)
+    pub labels: Array2<f32>,   // shape (rows, 1)
+}
+
+impl ArrayDataset {
+    pub fn new(features: Array2<f32>, labels: Array2<f32>) -> Self {
+        assert_eq!(features.nrows(), labels.nrows(), "Features and labels must have the same number of rows");
+        ArrayDataset { features, labels }
+    }
+}
+
+impl Dataset for ArrayDataset {
+    fn len(&self) -> usize {
+        self.features.nrows()
+    }
+
+    fn get_batch(&self, indices: &[usize]) -> Batch {
+        (self.features.select(Axis(0), indices), self.labels.select(Axis(0), indices))
+    }
+}
@@ -0,0 +1,87 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, Read};
+
+use flate2::read::GzDecoder;
+use ndarray::Array2;
+use super::ArrayDataset;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const UNSIGNED_BYTE: u8 = 0x08;
+
+// Reads an IDX file of unsigned bytes, plain or gzip-compressed.
+// returns dimensions and the data in row-major order
+pub fn read_idx(path: &str) -> Result<(Vec<usize>, Vec<u8>), Box<dyn Error>> {
+    let mut raw = Vec::new();
+    BufReader::new(File::open(path)?).read_to_end(&mut raw)?;
+
+    let bytes = if raw.starts_with(&GZIP_MAGIC) {
+        let mut decoded = Vec::new();
+        GzDecoder::new(raw.as_slice()).read_to_end(&mut decoded)?;
+        decoded
+    } else {
+        raw
+    };
+
+    // magic number: two zero bytes, data type, number of dimensions
+    if bytes.len() < 4 || bytes[0] != 0 || bytes[1] != 0 {
+        return Err(format!("{}: not an IDX file", path).into());
+    }
+    if bytes[2] != UNSIGNED_BYTE {
+        return Err(format!("{}: unsupported IDX data type 0x{:02x}, only unsigned byte is supported", path, bytes[2]).into());
+    }
+
+    let ndims = bytes[3] as usize;
+    let header_len = 4 + 4 * ndims;
+    if bytes.len() < header_len {
+        return Err(format!("{}: truncated IDX header", path).into());
+    }
+    let dims: Vec<usize> = bytes[4..header_len]
+        .chunks_exact(4)
+        .map(|d| u32::from_be_bytes([d[0], d[1], d[2], d[3]]) as usize)
+        .collect();
+
+    // sizes come from the file, so a corrupt header mustn't wrap around
+    let len = dims.iter()
+        .try_fold(1usize, |len, &d| len.checked_mul(d))
+        .ok_or_else(|| format!("{}: dimensions {:?} overflow the addressable size", path, dims))?;
+    if bytes.len() - header_len != len {
+        return Err(format!("{}: expected {} values for dimensions {:?}, found {}", path, len, dims, bytes.len() - header_len).into());
+    }
+
+    Ok((dims, bytes[header_len..].to_vec()))
+}
+
+// `*-images-idx3-ubyte` as shape (number of images, rows * cols), pixel values from 0 - 255
+pub fn read_idx_images(path: &str) -> Result<Array2<f32>, Box<dyn Error>> {
+    let (dims, data) = read_idx(path)?;
+    if dims.len() < 2 {
+        return Err(format!("{}: expected image dimensions, found {:?}", path, dims).into());
+    }
+    let num_images = dims[0];
+    let pixels = dims[1..].iter().product();
+
+    let data = data.into_iter().map(f32::from).collect();
+    Ok(Array2::from_shape_vec((num_images, pixels), data)?)
+}
+
+// `*-labels-idx1-ubyte` as shape (number of labels, 1)
+pub fn read_idx_labels(path: &str) -> Result<Array2<f32>, Box<dyn Error>> {
+    let (dims, data) = read_idx(path)?;
+    if dims.len() != 1 {
+        return Err(format!("{}: expected 1 dimension for labels, found {:?}", path, dims).into());
+    }
+
+    let data = data.into_iter().map(f32::from).collect();
+    Ok(Array2::from_shape_vec((dims[0], 1), data)?)
+}
+
+// Images and labels of an MNIST-style IDX pair as a dataset
+pub fn load_idx_dataset(images_path: &str, labels_path: &str) -> Result<ArrayDataset, Box<dyn Error>> {
+    let features = read_idx_images(images_path)?;
+    let labels = read_idx_labels(labels_path)?;
+    if features.nrows() != labels.nrows() {
+        return Err(format!("{} images but {} labels", features.nrows(), labels.nrows()).into());
+    }
+    Ok(ArrayDataset::new(features, labels))
+}
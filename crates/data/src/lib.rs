@@ -0,0 +1,50 @@
+use ndarray::Array2;
+pub use dataset::ArrayDataset;
+pub use csvdataset::CsvDataset;
+pub use dataloader::{DataLoader, BatchIter, Batches};
+pub use streaming::{StreamingLoader, StreamingBatches, FileFormat};
+pub use preprocessing::{Transform, StandardScaler, MinMaxScaler, RobustScaler, SimpleImputer, Imputation, OneHotEncoder};
+pub use split::{train_test_split, train_val_test_split, k_fold, stratified_k_fold, Fold};
+pub use augmentation::{Augmenter, Augmentation};
+pub use idx::{read_idx, read_idx_images, read_idx_labels, load_idx_dataset};
+
+// features of shape (batch size, number of features), labels of shape (batch size, 1)
+pub type Batch = (Array2<f32>, Array2<f32>);
+
+// Random streams derived from a user seed, kept apart so that no two of them start from the same state
+pub(crate) const SHUFFLE_STREAM: u64 = 1;
+pub(crate) const AUGMENT_STREAM: u64 = 2;
+
+// Seed of `stream` in `epoch`. Every input goes through a SplitMix64 round, so neighbouring seeds,
+// epochs and streams give unrelated generators, unlike `seed + epoch` where seed 1 epoch 0 equals seed 0 epoch 1.
+pub(crate) fn derive_seed(seed: u64, stream: u64, epoch: u64) -> u64 {
+    splitmix64(splitmix64(splitmix64(seed) ^ stream) ^ epoch)
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+pub trait Dataset: Send + Sync {
+    // returns number of rows
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // returns features and labels of rows at `indices`, in that order
+    fn get_batch(&self, indices: &[usize]) -> Batch;
+}
+
+pub mod dataset;
+pub mod csvdataset;
+pub mod dataloader;
+pub mod idx;
+pub mod streaming;
+pub mod preprocessing;
+pub mod split;
+pub mod augmentation;
@@ -0,0 +1,358 @@
+use std::error::Error;
+use std::fs::File;
+
+use ndarray::{Array1, Array2, Axis};
+use polars::prelude::*;
+
+// Feature transform fitted on training data and applied unchanged at inference.
+// Missing values are NaN, they are ignored while fitting and passed through by scalers.
+pub trait Transform {
+    fn fit(&mut self, x: &Array2<f32>);
+
+    fn transform(&self, x: &Array2<f32>) -> Array2<f32>;
+
+    fn fit_transform(&mut self, x: &Array2<f32>) -> Array2<f32> {
+        self.fit(x);
+        self.transform(x)
+    }
+
+    // stats are written to `{save_path}/{name}.csv`, next to the weights written by `save_model`
+    fn save(&self, save_path: &str, name: &str) -> Result<(), Box<dyn Error>>;
+}
+
+// (x - mean) / std
+pub struct StandardScaler {
+    pub mean: Array1<f32>,
+    pub std: Array1<f32>,
+}
+
+// (x - min) / (max - min), to range [0, 1]
+pub struct MinMaxScaler {
+    pub min: Array1<f32>,
+    pub max: Array1<f32>,
+}
+
+// (x - median) / (q3 - q1), robust to outliers
+pub struct RobustScaler {
+    pub median: Array1<f32>,
+    pub iqr: Array1<f32>,
+}
+
+pub enum Imputation {
+    Mean,
+    Median,
+    Constant(f32),
+}
+
+// Replaces NaN with a per-feature value
+pub struct SimpleImputer {
+    pub strategy: Imputation,
+    pub fill: Array1<f32>,
+}
+
+impl StandardScaler {
+    pub fn new() -> Self {
+        StandardScaler { mean: Array1::zeros(0), std: Array1::zeros(0) }
+    }
+
+    pub fn load(save_path: &str, name: &str) -> Result<Self, Box<dyn Error>> {
+        let mut stats = load_stats(save_path, name, &["mean", "std"])?;
+        let std = stats.pop().unwrap();
+        let mean = stats.pop().unwrap();
+        Ok(StandardScaler { mean, std })
+    }
+}
+
+impl Default for StandardScaler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transform for StandardScaler {
+    fn fit(&mut self, x: &Array2<f32>) {
+        let cols = column_values(x);
+        self.mean = cols.iter().map(|c| mean(c)).collect();
+        self.std = cols
+            .iter()
+            .zip(self.mean.iter())
+            .map(|(c, m)| {
+                let var = c.iter().map(|v| (v - m).powi(2)).sum::<f32>() / c.len().max(1) as f32;
+                non_zero(var.sqrt())
+            })
+            .collect();
+    }
+
+    fn transform(&self, x: &Array2<f32>) -> Array2<f32> {
+        (x - &self.mean) / &self.std
+    }
+
+    fn save(&self, save_path: &str, name: &str) -> Result<(), Box<dyn Error>> {
+        save_stats(save_path, name, &[("mean", &self.mean), ("std", &self.std)])
+    }
+}
+
+impl MinMaxScaler {
+    pub fn new() -> Self {
+        MinMaxScaler { min: Array1::zeros(0), max: Array1::zeros(0) }
+    }
+
+    pub fn load(save_path: &str, name: &str) -> Result<Self, Box<dyn Error>> {
+        let mut stats = load_stats(save_path, name, &["min", "max"])?;
+        let max = stats.pop().unwrap();
+        let min = stats.pop().unwrap();
+        Ok(MinMaxScaler { min, max })
+    }
+}
+
+impl Default for MinMaxScaler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transform for MinMaxScaler {
+    fn fit(&mut self, x: &Array2<f32>) {
+        let cols = column_values(x);
+        // columns without a value are left unscaled, like constant ones
+        self.min = cols.iter().map(|c| if c.is_empty() { 0.0 } else { c.iter().cloned().fold(f32::INFINITY, f32::min) }).collect();
+        self.max = cols.iter().map(|c| if c.is_empty() { 1.0 } else { c.iter().cloned().fold(f32::NEG_INFINITY, f32::max) }).collect();
+    }
+
+    fn transform(&self, x: &Array2<f32>) -> Array2<f32> {
+        let range = (&self.max - &self.min).mapv(non_zero);
+        (x - &self.min) / range
+    }
+
+    fn save(&self, save_path: &str, name: &str) -> Result<(), Box<dyn Error>> {
+        save_stats(save_path, name, &[("min", &self.min), ("max", &self.max)])
+    }
+}
+
+impl RobustScaler {
+    pub fn new() -> Self {
+        RobustScaler { median: Array1::zeros(0), iqr: Array1::zeros(0) }
+    }
+
+    pub fn load(save_path: &str, name: &str) -> Result<Self, Box<dyn Error>> {
+        let mut stats = load_stats(save_path, name, &["median", "iqr"])?;
+        let iqr = stats.pop().unwrap();
+        let median = stats.pop().unwrap();
+        Ok(RobustScaler { median, iqr })
+    }
+}
+
+impl Default for RobustScaler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transform for RobustScaler {
+    fn fit(&mut self, x: &Array2<f32>) {
+        let cols = column_values(x);
+        self.median = cols.iter().map(|c| quantile(c, 0.5)).collect();
+        self.iqr = cols.iter().map(|c| non_zero(quantile(c, 0.75) - quantile(c, 0.25))).collect();
+    }
+
+    fn transform(&self, x: &Array2<f32>) -> Array2<f32> {
+        (x - &self.median) / &self.iqr
+    }
+
+    fn save(&self, save_path: &str, name: &str) -> Result<(), Box<dyn Error>> {
+        save_stats(save_path, name, &[("median", &self.median), ("iqr", &self.iqr)])
+    }
+}
+
+impl SimpleImputer {
+    pub fn new(strategy: Imputation) -> Self {
+        SimpleImputer { strategy, fill: Array1::zeros(0) }
+    }
+
+    // strategy is not needed once fitted, loaded imputers use the stored fill values
+    pub fn load(save_path: &str, name: &str) -> Result<Self, Box<dyn Error>> {
+        let fill = load_stats(save_path, name, &["fill"])?.pop().unwrap();
+        Ok(SimpleImputer { strategy: Imputation::Constant(0.0), fill })
+    }
+}
+
+impl Transform for SimpleImputer {
+    fn fit(&mut self, x: &Array2<f32>) {
+        let cols = column_values(x);
+        self.fill = match self.strategy {
+            Imputation::Mean => cols.iter().map(|c| mean(c)).collect(),
+            Imputation::Median => cols.iter().map(|c| quantile(c, 0.5)).collect(),
+            Imputation::Constant(value) => Array1::from_elem(x.ncols(), value),
+        };
+    }
+
+    fn transform(&self, x: &Array2<f32>) -> Array2<f32> {
+        let mut out = x.clone();
+        for (mut col, &fill) in out.axis_iter_mut(Axis(1)).zip(self.fill.iter()) {
+            col.mapv_inplace(|v| if v.is_nan() { fill } else { v });
+        }
+        out
+    }
+
+    fn save(&self, save_path: &str, name: &str) -> Result<(), Box<dyn Error>> {
+        save_stats(save_path, name, &[("fill", &self.fill)])
+    }
+}
+
+// One-hot encoding of categorical (string or categorical dtype) polars columns.
+// Categories unseen while fitting are encoded as all zeros.
+pub struct OneHotEncoder {
+    // column name, sorted categories
+    pub categories: Vec<(String, Vec<String>)>,
+}
+
+impl OneHotEncoder {
+    pub fn new() -> Self {
+        OneHotEncoder { categories: Vec::new() }
+    }
+
+    pub fn fit(&mut self, df: &DataFrame, columns: &[&str]) -> Result<(), Box<dyn Error>> {
+        self.categories.clear();
+        for &name in columns {
+            let values = column_strings(df, name)?;
+            let mut cats: Vec<String> = values.into_iter().flatten().collect();
+            cats.sort();
+            cats.dedup();
+            self.categories.push((name.to_string(), cats));
+        }
+        Ok(())
+    }
+
+    // returns shape (rows, total number of categories), columns in the order they were fitted
+    pub fn transform(&self, df: &DataFrame) -> Result<Array2<f32>, Box<dyn Error>> {
+        let width = self.categories.iter().map(|(_, c)| c.len()).sum();
+        let mut out = Array2::<f32>::zeros((df.height(), width));
+
+        let mut offset = 0;
+        for (name, cats) in self.categories.iter() {
+            for (row, value) in column_strings(df, name)?.into_iter().enumerate() {
+                if let Some(idx) = value.and_then(|v| cats.binary_search(&v).ok()) {
+                    out[[row, offset + idx]] = 1.0;
+                }
+            }
+            offset += cats.len();
+        }
+        Ok(out)
+    }
+
+    pub fn fit_transform(&mut self, df: &DataFrame, columns: &[&str]) -> Result<Array2<f32>, Box<dyn Error>> {
+        self.fit(df, columns)?;
+        self.transform(df)
+    }
+
+    // One row per category, a column fitted without categories is a single row with a missing category
+    pub fn save(&self, save_path: &str, name: &str) -> Result<(), Box<dyn Error>> {
+        let (cols, cats): (Vec<&str>, Vec<Option<&str>>) = self.categories
+            .iter()
+            .flat_map(|(col, cats)| {
+                let rows: Vec<Option<&str>> = if cats.is_empty() { vec![None] } else { cats.iter().map(|c| Some(c.as_str())).collect() };
+                rows.into_iter().map(move |c| (col.as_str(), c))
+            })
+            .unzip();
+        let mut df = DataFrame::new(vec![
+            Column::new(PlSmallStr::from("column"), cols),
+            Column::new(PlSmallStr::from("category"), cats),
+        ])?;
+        write_csv(&mut df, save_path, name)
+    }
+
+    pub fn load(save_path: &str, name: &str) -> Result<Self, Box<dyn Error>> {
+        let df = read_csv(save_path, name, Some(0))?;
+        let cols = df.column("column")?.str()?.clone();
+        let cats = df.column("category")?.str()?.clone();
+
+        let mut categories: Vec<(String, Vec<String>)> = Vec::new();
+        for (col, cat) in cols.into_iter().zip(&cats) {
+            let col = col.unwrap_or_default();
+            match (categories.last_mut(), cat) {
+                (Some((name, cats)), Some(cat)) if name == col => cats.push(cat.to_string()),
+                (_, cat) => categories.push((col.to_string(), cat.map(str::to_string).into_iter().collect())),
+            }
+        }
+        Ok(OneHotEncoder { categories })
+    }
+}
+
+impl Default for OneHotEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// values of every column with NaN removed
+fn column_values(x: &Array2<f32>) -> Vec<Vec<f32>> {
+    x.axis_iter(Axis(1))
+        .map(|c| c.iter().cloned().filter(|v| !v.is_nan()).collect())
+        .collect()
+}
+
+fn mean(values: &[f32]) -> f32 {
+    values.iter().sum::<f32>() / values.len().max(1) as f32
+}
+
+// linear interpolation between closest ranks
+fn quantile(values: &[f32], q: f32) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let pos = q * (sorted.len() - 1) as f32;
+    let (lo, hi) = (pos.floor() as usize, pos.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f32)
+}
+
+// constant features would divide by zero, leave them unscaled
+fn non_zero(x: f32) -> f32 {
+    if x == 0.0 { 1.0 } else { x }
+}
+
+fn column_strings(df: &DataFrame, name: &str) -> Result<Vec<Option<String>>, Box<dyn Error>> {
+    let col = df.column(name)?.cast(&DataType::String)?;
+    Ok(col.str()?.into_iter().map(|v| v.map(str::to_string)).collect())
+}
+
+fn save_stats(save_path: &str, name: &str, stats: &[(&str, &Array1<f32>)]) -> Result<(), Box<dyn Error>> {
+    let columns = stats
+        .iter()
+        .map(|(stat, values)| Column::new(PlSmallStr::from(*stat), values.to_vec()))
+        .collect();
+    let mut df = DataFrame::new(columns)?;
+    write_csv(&mut df, save_path, name)
+}
+
+fn load_stats(save_path: &str, name: &str, stats: &[&str]) -> Result<Vec<Array1<f32>>, Box<dyn Error>> {
+    let df = read_csv(save_path, name, None)?;
+    stats
+        .iter()
+        .map(|stat| {
+            let col = df.column(stat)?.cast(&DataType::Float32)?;
+            Ok(col.f32()?.into_iter().map(|v| v.unwrap_or(f32::NAN)).collect())
+        })
+        .collect()
+}
+
+fn write_csv(df: &mut DataFrame, save_path: &str, name: &str) -> Result<(), Box<dyn Error>> {
+    let mut file = File::create(format!("{}/{}.csv", save_path, name))?;
+    CsvWriter::new(&mut file)
+        .include_header(true)
+        .with_separator(b',')
+        .finish(df)?;
+    Ok(())
+}
+
+// `infer_schema_length` of Some(0) reads every column as string
+fn read_csv(save_path: &str, name: &str, infer_schema_length: Option<usize>) -> Result<DataFrame, Box<dyn Error>> {
+    let mut options = CsvReadOptions::default().with_has_header(true);
+    if infer_schema_length.is_some() {
+        options = options.with_infer_schema_length(infer_schema_length);
+    }
+    Ok(options
+        .try_into_reader_with_file_path(Some(format!("{}/{}.csv", save_path, name).into()))?
+        .finish()?)
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use ndarray::{Array2, Axis};
+use ndarray_rand::rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+use super::Batch;
+
+// train indices, validation indices
+pub type Fold = (Vec<usize>, Vec<usize>);
+
+// Seeded random split into (train, test). With `stratify` every label keeps its share in both parts.
+pub fn train_test_split(x: &Array2<f32>, y: &Array2<f32>, test_fraction: f32, seed: u64, stratify: bool) -> (Batch, Batch) {
+    assert!((0.0..=1.0).contains(&test_fraction), "Test fraction must be between 0 and 1");
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (train, test) = split_indices(y, test_fraction, &mut rng, stratify);
+    (select_rows(x, y, &train), select_rows(x, y, &test))
+}
+
+// Seeded random split into (train, validation, test), fractions are of the whole dataset
+pub fn train_val_test_split(x: &Array2<f32>, y: &Array2<f32>, val_fraction: f32, test_fraction: f32, seed: u64, stratify: bool) -> (Batch, Batch, Batch) {
+    assert!(val_fraction + test_fraction <= 1.0, "Validation and test fractions must not exceed 1");
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (rest, test) = split_indices(y, test_fraction, &mut rng, stratify);
+
+    let rest_y = y.select(Axis(0), &rest);
+    let val_of_rest = if test_fraction < 1.0 { val_fraction / (1.0 - test_fraction) } else { 0.0 };
+    let (train, val) = split_indices(&rest_y, val_of_rest.min(1.0), &mut rng, stratify);
+    let train: Vec<usize> = train.into_iter().map(|i| rest[i]).collect();
+    let val: Vec<usize> = val.into_iter().map(|i| rest[i]).collect();
+
+    (select_rows(x, y, &train), select_rows(x, y, &val), select_rows(x, y, &test))
+}
+
+// `k` folds over shuffled rows, every row is in exactly one validation set
+pub fn k_fold(num_rows: usize, k: usize, seed: u64) -> Vec<Fold> {
+    assert!(k >= 2 && k <= num_rows, "Number of folds must be between 2 and the number of rows");
+    let mut order: Vec<usize> = (0..num_rows).collect();
+    order.shuffle(&mut StdRng::seed_from_u64(seed));
+
+    let assignment: Vec<usize> = (0..num_rows).map(|i| i % k).collect();
+    folds_from_assignment(&order, &assignment, k)
+}
+
+// `k` folds where every label is spread evenly over the validation sets
+pub fn stratified_k_fold(y: &Array2<f32>, k: usize, seed: u64) -> Vec<Fold> {
+    assert!(k >= 2 && k <= y.nrows(), "Number of folds must be between 2 and the number of rows");
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // deal rows of each label round robin, continuing where the previous label stopped
+    let mut order = Vec::with_capacity(y.nrows());
+    let mut assignment = Vec::with_capacity(y.nrows());
+    let mut next_fold = 0;
+    for mut rows in group_by_label(y).into_values() {
+        rows.shuffle(&mut rng);
+        for row in rows {
+            order.push(row);
+            assignment.push(next_fold);
+            next_fold = (next_fold + 1) % k;
+        }
+    }
+    folds_from_assignment(&order, &assignment, k)
+}
+
+fn split_indices(y: &Array2<f32>, test_fraction: f32, rng: &mut StdRng, stratify: bool) -> (Vec<usize>, Vec<usize>) {
+    let groups = if stratify {
+        group_by_label(y).into_values().collect()
+    } else {
+        vec![(0..y.nrows()).collect::<Vec<usize>>()]
+    };
+
+    let mut train = Vec::new();
+    let mut test = Vec::new();
+    for mut rows in groups {
+        rows.shuffle(rng);
+        let num_test = (rows.len() as f32 * test_fraction).round() as usize;
+        test.extend_from_slice(&rows[..num_test]);
+        train.extend_from_slice(&rows[num_test..]);
+    }
+    // stratified groups come out ordered by label
+    train.shuffle(rng);
+    test.shuffle(rng);
+    (train, test)
+}
+
+// row indices of every label, ordered by label for reproducibility
+fn group_by_label(y: &Array2<f32>) -> BTreeMap<i64, Vec<usize>> {
+    let mut groups: BTreeMap<i64, Vec<usize>> = BTreeMap::new();
+    for (row, label) in y.column(0).iter().enumerate() {
+        groups.entry(*label as i64).or_default().push(row);
+    }
+    groups
+}
+
+fn folds_from_assignment(order: &[usize], assignment: &[usize], k: usize) -> Vec<Fold> {
+    (0..k)
+        .map(|fold| {
+            let mut train = Vec::new();
+            let mut val = Vec::new();
+            for (&row, &f) in order.iter().zip(assignment.iter()) {
+                if f == fold {
+                    val.push(row);
+                } else {
+                    train.push(row);
+                }
+            }
+            (train, val)
+        })
+        .collect()
+}
+
+fn select_rows(x: &Array2<f32>, y: &Array2<f32>, rows: &[usize]) -> Batch {
+    (x.select(Axis(0), rows), y.select(Axis(0), rows))
+}
@@ -0,0 +1,210 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fs::File;
+
+use ndarray::Array2;
+use ndarray_rand::rand::{Rng, SeedableRng, rngs::StdRng};
+use polars::io::mmap::MmapBytesReader;
+use polars::prelude::*;
+use super::{Batch, SHUFFLE_STREAM, derive_seed};
+
+pub enum FileFormat {
+    Csv,
+    Parquet,
+}
+
+// Reads batches from a CSV or Parquet file chunk by chunk, the whole file is never held in memory
+pub struct StreamingLoader {
+    pub path: String,
+    pub format: FileFormat,
+    pub label_col: String,
+    // None takes every column except `label_col`
+    pub feature_cols: Option<Vec<String>>,
+    pub batch_size: usize,
+    // rows read from the file at a time
+    pub chunk_rows: usize,
+    // rows to draw batches from at random, 0 keeps the file order
+    pub shuffle_buffer: usize,
+    pub seed: u64,
+    pub drop_last: bool,
+    pub epoch: u64,
+}
+
+impl StreamingLoader {
+    pub fn new(path: &str, format: FileFormat, label_col: &str, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "Batch size must be positive");
+        StreamingLoader {
+            path: path.to_string(),
+            format,
+            label_col: label_col.to_string(),
+            feature_cols: None,
+            batch_size,
+            chunk_rows: 50_000,
+            shuffle_buffer: 0,
+            seed: 0,
+            drop_last: false,
+            epoch: 0,
+        }
+    }
+
+    // Batches of one pass over the file. Shuffling order changes with every epoch.
+    pub fn iter(&mut self) -> Result<StreamingBatches, Box<dyn Error>> {
+        let source = match self.format {
+            FileFormat::Csv => {
+                // read every column as string and cast per chunk, schema inferred from the first rows could break later chunks
+                let file: Box<dyn MmapBytesReader> = Box::new(File::open(&self.path)?);
+                let reader = CsvReadOptions::default()
+                    .with_has_header(true)
+                    .with_infer_schema_length(Some(0))
+                    .with_chunk_size(self.chunk_rows)
+                    .into_reader_with_file_handle(file)
+                    .batched(None)?;
+                ChunkSource::Csv(reader)
+            }
+            FileFormat::Parquet => {
+                let scan = LazyFrame::scan_parquet(self.path.as_str(), ScanArgsParquet::default())?;
+                ChunkSource::Parquet { scan, offset: 0 }
+            }
+        };
+
+        let batches = StreamingBatches {
+            source: Some(source),
+            label_col: self.label_col.clone(),
+            feature_cols: self.feature_cols.clone(),
+            batch_size: self.batch_size,
+            chunk_rows: self.chunk_rows,
+            shuffle_buffer: self.shuffle_buffer,
+            drop_last: self.drop_last,
+            rng: StdRng::seed_from_u64(derive_seed(self.seed, SHUFFLE_STREAM, self.epoch)),
+            buffer: VecDeque::new(),
+            rows_read: 0,
+        };
+        self.epoch += 1;
+        Ok(batches)
+    }
+}
+
+#[allow(clippy::large_enum_variant)]
+enum ChunkSource {
+    Csv(OwnedBatchedCsvReader),
+    Parquet { scan: LazyFrame, offset: usize },
+}
+
+impl ChunkSource {
+    // returns None once the file is exhausted
+    fn next_chunk(&mut self, chunk_rows: usize) -> PolarsResult<Option<DataFrame>> {
+        match self {
+            ChunkSource::Csv(reader) => Ok(reader.next_batches(1)?.and_then(|mut dfs| dfs.pop())),
+            ChunkSource::Parquet { scan, offset } => {
+                // slice is pushed down to the reader, only the needed row groups are read
+                let df = scan.clone().slice(*offset as i64, chunk_rows as IdxSize).collect()?;
+                *offset += df.height();
+                Ok(if df.height() == 0 { None } else { Some(df) })
+            }
+        }
+    }
+}
+
+pub struct StreamingBatches {
+    source: Option<ChunkSource>,
+    label_col: String,
+    feature_cols: Option<Vec<String>>,
+    batch_size: usize,
+    chunk_rows: usize,
+    shuffle_buffer: usize,
+    drop_last: bool,
+    rng: StdRng,
+    // rows read but not yet handed out as (features, label)
+    buffer: VecDeque<(Vec<f32>, f32)>,
+    // rows read from the file so far, to point errors at the right row
+    rows_read: usize,
+}
+
+impl StreamingBatches {
+    fn fill_buffer(&mut self) -> Result<(), Box<dyn Error>> {
+        let target = self.shuffle_buffer.max(self.batch_size);
+        while self.buffer.len() < target {
+            let Some(source) = self.source.as_mut() else {
+                break;
+            };
+            let Some(chunk) = source.next_chunk(self.chunk_rows)? else {
+                self.source = None;
+                break;
+            };
+
+            let (features, labels) = chunk_to_arrays(&chunk, &self.label_col, self.feature_cols.as_deref(), self.rows_read)?;
+            self.rows_read += chunk.height();
+            for (row, label) in features.outer_iter().zip(labels.iter()) {
+                self.buffer.push_back((row.to_vec(), *label));
+            }
+        }
+        Ok(())
+    }
+
+    fn next_batch(&mut self) -> Result<Option<Batch>, Box<dyn Error>> {
+        self.fill_buffer()?;
+
+        let size = self.batch_size.min(self.buffer.len());
+        if size == 0 || (self.drop_last && size < self.batch_size) {
+            return Ok(None);
+        }
+
+        let mut rows = Vec::with_capacity(size);
+        for _ in 0..size {
+            let row = if self.shuffle_buffer > 0 {
+                let idx = self.rng.gen_range(0..self.buffer.len());
+                self.buffer.swap_remove_back(idx)
+            } else {
+                self.buffer.pop_front()
+            };
+            rows.push(row.unwrap());
+        }
+
+        let num_features = rows[0].0.len();
+        let labels = Array2::from_shape_vec((size, 1), rows.iter().map(|(_, l)| *l).collect())?;
+        let features = Array2::from_shape_vec((size, num_features), rows.into_iter().flat_map(|(f, _)| f).collect())?;
+        Ok(Some((features, labels)))
+    }
+}
+
+impl Iterator for StreamingBatches {
+    type Item = Result<Batch, Box<dyn Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_batch().transpose()
+    }
+}
+
+// `first_row` is the row of the file the chunk starts at
+fn chunk_to_arrays(df: &DataFrame, label_col: &str, feature_cols: Option<&[String]>, first_row: usize) -> Result<Batch, Box<dyn Error>> {
+    let labels = df.select([label_col])?;
+    let features = match feature_cols {
+        Some(cols) => df.select(cols.iter().map(|c| c.as_str()))?,
+        None => df.drop(label_col)?,
+    };
+    Ok((to_f32_ndarray(&features, first_row)?, to_f32_ndarray(&labels, first_row)?))
+}
+
+fn to_f32_ndarray(df: &DataFrame, first_row: usize) -> Result<Array2<f32>, Box<dyn Error>> {
+    let columns = df
+        .get_columns()
+        .iter()
+        .map(|c| to_f32_column(c, first_row))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(DataFrame::new(columns)?.to_ndarray::<Float32Type>(IndexOrder::C)?)
+}
+
+// Missing cells become NaN, cells that aren't numbers are an error naming their row and column
+// instead of silently turning into NaN somewhere in a large file
+fn to_f32_column(column: &Column, first_row: usize) -> Result<Column, Box<dyn Error>> {
+    if let Ok(cast) = column.strict_cast(&DataType::Float32) {
+        return Ok(cast);
+    }
+    let lenient = column.cast(&DataType::Float32)?;
+    let malformed = column.is_not_null() & lenient.is_null();
+    let row = malformed.into_iter().position(|m| m == Some(true)).unwrap_or(0);
+    Err(format!(
+        "column {}, row {}: can't read {} as a number",
+        column.name(), first_row + row, column.get(row)?
+    ).into())
+}
@@ -0,0 +1,98 @@
+use data::*;
+use ndarray::Array2;
+use ndarray_rand::rand::{Rng, SeedableRng, rngs::StdRng};
+
+const SIZE: usize = 9;
+
+fn augmenter(augmentation: Augmentation, seed: u64) -> Augmenter {
+    Augmenter::new(SIZE, SIZE, vec![augmentation], seed).unwrap()
+}
+
+// 1 at (row, col), 0 elsewhere
+fn dot(row: usize, col: usize) -> Array2<f32> {
+    let mut image = Array2::zeros((1, SIZE * SIZE));
+    image[[0, row * SIZE + col]] = 1.0;
+    image
+}
+
+fn centroid(image: &Array2<f32>) -> (f32, f32) {
+    let total: f32 = image.sum();
+    let (mut r, mut c) = (0.0, 0.0);
+    for (i, p) in image.iter().enumerate() {
+        r += (i / SIZE) as f32 * p;
+        c += (i % SIZE) as f32 * p;
+    }
+    (r / total, c / total)
+}
+
+#[test]
+fn translate_shifts_by_the_drawn_offset() {
+    let seed = 11;
+    let out = augmenter(Augmentation::Translate { max_shift: 2.0 }, seed).apply(&dot(4, 4));
+
+    // same draws as the augmenter, rows first
+    let mut rng = StdRng::seed_from_u64(seed);
+    let dy: f32 = rng.gen_range(-2.0..=2.0);
+    let dx: f32 = rng.gen_range(-2.0..=2.0);
+
+    let (r, c) = centroid(&out);
+    assert!((out.sum() - 1.0).abs() < 1e-5);
+    assert!((r - (4.0 + dy)).abs() < 1e-4, "{} vs {}", r, 4.0 + dy);
+    assert!((c - (4.0 + dx)).abs() < 1e-4, "{} vs {}", c, 4.0 + dx);
+}
+
+#[test]
+fn random_erasing_zeroes_a_rectangle() {
+    let erasing = Augmentation::RandomErasing { probability: 1.0, min_area: 0.1, max_area: 0.3 };
+    let out = augmenter(erasing, 3).apply(&Array2::ones((1, SIZE * SIZE)));
+
+    let zeros: Vec<(usize, usize)> =
+        out.iter().enumerate().filter(|&(_, p)| *p == 0.0).map(|(i, _)| (i / SIZE, i % SIZE)).collect();
+    assert!(!zeros.is_empty());
+    let (top, bottom) = (zeros.iter().map(|z| z.0).min().unwrap(), zeros.iter().map(|z| z.0).max().unwrap());
+    let (left, right) = (zeros.iter().map(|z| z.1).min().unwrap(), zeros.iter().map(|z| z.1).max().unwrap());
+    // everything inside the bounding box is erased and nothing else changed
+    assert_eq!(zeros.len(), (bottom - top + 1) * (right - left + 1));
+    assert!(out.iter().all(|&p| p == 0.0 || p == 1.0));
+}
+
+#[test]
+fn random_erasing_with_zero_probability_keeps_the_image() {
+    let erasing = Augmentation::RandomErasing { probability: 0.0, min_area: 0.1, max_area: 0.3 };
+    let image = Array2::ones((1, SIZE * SIZE));
+    assert_eq!(augmenter(erasing, 3).apply(&image), image);
+}
+
+#[test]
+fn rotate_by_zero_is_identity() {
+    let image = Array2::from_shape_fn((2, SIZE * SIZE), |(i, j)| ((i * 31 + j) as f32 * 0.37).sin());
+    let out = augmenter(Augmentation::Rotate { max_degrees: 0.0 }, 5).apply(&image);
+    for (a, b) in out.iter().zip(image.iter()) {
+        assert!((a - b).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn invalid_parameters_are_errors() {
+    let invalid = [
+        Augmentation::Translate { max_shift: -1.0 },
+        Augmentation::Rotate { max_degrees: f32::NAN },
+        Augmentation::Scale { min: 1.2, max: 0.8 },
+        Augmentation::Scale { min: 0.0, max: 1.0 },
+        Augmentation::Elastic { alpha: 1.0, sigma: 0.0 },
+        Augmentation::GaussianNoise { std: -0.1 },
+        Augmentation::RandomErasing { probability: 1.5, min_area: 0.1, max_area: 0.2 },
+        Augmentation::RandomErasing { probability: 0.5, min_area: 0.3, max_area: 0.2 },
+    ];
+    for augmentation in invalid {
+        assert!(Augmenter::new(SIZE, SIZE, vec![augmentation.clone()], 0).is_err(), "{:?}", augmentation);
+    }
+    assert!(Augmenter::new(0, SIZE, vec![], 0).is_err());
+}
+
+#[test]
+fn elastic_output_is_finite() {
+    let image = Array2::from_shape_fn((1, SIZE * SIZE), |(_, j)| j as f32);
+    let out = augmenter(Augmentation::Elastic { alpha: 2.0, sigma: 1.0 }, 8).apply(&image);
+    assert!(out.iter().all(|p| p.is_finite()));
+}
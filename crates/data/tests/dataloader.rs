@@ -0,0 +1,98 @@
+use data::*;
+use ndarray::Array2;
+
+// row i has feature i and label 10 * i
+fn loader(rows: usize, batch_size: usize) -> DataLoader<ArrayDataset> {
+    let features = Array2::from_shape_fn((rows, 1), |(i, _)| i as f32);
+    let labels = features.mapv(|i| 10.0 * i);
+    DataLoader::new(ArrayDataset::new(features, labels), batch_size)
+}
+
+fn batches(loader: &mut DataLoader<ArrayDataset>) -> Vec<Batch> {
+    loader.iter().collect()
+}
+
+fn sizes(batches: &[Batch]) -> Vec<usize> {
+    batches.iter().map(|(x, _)| x.nrows()).collect()
+}
+
+#[test]
+fn batches_cover_the_dataset_in_order() {
+    let mut loader = loader(10, 4);
+    let batches = batches(&mut loader);
+    assert_eq!(sizes(&batches), vec![4, 4, 2]);
+    assert_eq!(loader.num_batches(), 3);
+
+    let rows: Vec<f32> = batches.iter().flat_map(|(x, _)| x.column(0).to_vec()).collect();
+    assert_eq!(rows, (0..10).map(|i| i as f32).collect::<Vec<_>>());
+    // labels stay with their rows
+    for (x, y) in batches.iter() {
+        assert_eq!(x.mapv(|i| 10.0 * i), y);
+    }
+}
+
+#[test]
+fn last_partial_batch_holds_the_remainder() {
+    let mut loader = loader(10, 4);
+    let (x, _) = batches(&mut loader).pop().unwrap();
+    assert_eq!(x.column(0).to_vec(), vec![8.0, 9.0]);
+}
+
+#[test]
+fn drop_last_skips_the_partial_batch() {
+    let mut loader = loader(10, 4);
+    loader.drop_last = true;
+    assert_eq!(sizes(&batches(&mut loader)), vec![4, 4]);
+    assert_eq!(loader.num_batches(), 2);
+
+    // nothing to drop when the batch size divides the dataset
+    let mut loader = self::loader(8, 4);
+    loader.drop_last = true;
+    assert_eq!(sizes(&batches(&mut loader)), vec![4, 4]);
+}
+
+#[test]
+fn batch_larger_than_dataset() {
+    let mut loader = loader(3, 8);
+    assert_eq!(sizes(&batches(&mut loader)), vec![3]);
+    loader.drop_last = true;
+    assert!(batches(&mut loader).is_empty());
+    assert_eq!(loader.num_batches(), 0);
+}
+
+#[test]
+fn prefetch_yields_the_same_batches() {
+    for prefetch in [1, 2, 8] {
+        let (mut sequential, mut prefetching) = (loader(23, 5), loader(23, 5));
+        sequential.shuffle = Some(4);
+        prefetching.shuffle = Some(4);
+        prefetching.prefetch = prefetch;
+        // two epochs, each with its own order
+        for _ in 0..2 {
+            assert_eq!(batches(&mut sequential), batches(&mut prefetching));
+        }
+    }
+}
+
+#[test]
+fn stopping_early_with_prefetch() {
+    let mut loader = loader(100, 2);
+    loader.prefetch = 1;
+    // the background thread stops once the batches are dropped
+    assert_eq!(loader.iter().take(3).count(), 3);
+    assert_eq!(loader.iter().count(), 50);
+}
+
+#[test]
+fn shuffled_epochs_keep_every_row() {
+    let mut loader = loader(20, 6);
+    loader.shuffle = Some(1);
+    let epoch = |loader: &mut DataLoader<ArrayDataset>| -> Vec<f32> {
+        batches(loader).iter().flat_map(|(x, _)| x.column(0).to_vec()).collect()
+    };
+    let (first, second) = (epoch(&mut loader), epoch(&mut loader));
+    assert_ne!(first, second);
+    let mut sorted = first.clone();
+    sorted.sort_by(f32::total_cmp);
+    assert_eq!(sorted, (0..20).map(|i| i as f32).collect::<Vec<_>>());
+}
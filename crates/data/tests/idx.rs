@@ -0,0 +1,80 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use data::*;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+
+// header of an unsigned byte IDX file followed by `payload`
+fn idx_bytes(dims: &[u32], payload: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![0, 0, 0x08, dims.len() as u8];
+    for d in dims {
+        bytes.extend_from_slice(&d.to_be_bytes());
+    }
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+fn write_file(name: &str, bytes: &[u8]) -> String {
+    let path: PathBuf = std::env::temp_dir().join(format!("data-idx-test-{}-{}", std::process::id(), name));
+    std::fs::write(&path, bytes).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn reads_dimensions_and_values() {
+    let path = write_file("plain", &idx_bytes(&[2, 3], &[1, 2, 3, 4, 5, 6]));
+    let (dims, data) = read_idx(&path).unwrap();
+    assert_eq!(dims, vec![2, 3]);
+    assert_eq!(data, vec![1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn reads_gzip_compressed_files() {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&idx_bytes(&[3], &[7, 8, 9])).unwrap();
+    let path = write_file("gzip", &encoder.finish().unwrap());
+    let (dims, data) = read_idx(&path).unwrap();
+    assert_eq!(dims, vec![3]);
+    assert_eq!(data, vec![7, 8, 9]);
+}
+
+#[test]
+fn images_are_flattened_per_image() {
+    let path = write_file("images", &idx_bytes(&[2, 2, 2], &[0, 1, 2, 3, 4, 5, 6, 255]));
+    let images = read_idx_images(&path).unwrap();
+    assert_eq!(images.dim(), (2, 4));
+    assert_eq!(images.row(1).to_vec(), vec![4.0, 5.0, 6.0, 255.0]);
+}
+
+#[test]
+fn dataset_pairs_images_and_labels() {
+    let images = write_file("pair-images", &idx_bytes(&[2, 1, 2], &[1, 2, 3, 4]));
+    let labels = write_file("pair-labels", &idx_bytes(&[2], &[0, 9]));
+    let dataset = load_idx_dataset(&images, &labels).unwrap();
+    let (features, labels) = dataset.get_batch(&[1]);
+    assert_eq!(features.row(0).to_vec(), vec![3.0, 4.0]);
+    assert_eq!(labels[[0, 0]], 9.0);
+}
+
+#[test]
+fn rejects_short_payload() {
+    let path = write_file("short", &idx_bytes(&[2, 3], &[1, 2, 3]));
+    let err = read_idx(&path).unwrap_err().to_string();
+    assert!(err.contains("expected 6 values"), "{}", err);
+}
+
+#[test]
+fn rejects_overflowing_dimensions() {
+    let path = write_file("overflow", &idx_bytes(&[u32::MAX, u32::MAX, u32::MAX], &[0]));
+    let err = read_idx(&path).unwrap_err().to_string();
+    assert!(err.contains("overflow"), "{}", err);
+}
+
+#[test]
+fn rejects_other_data_types() {
+    let mut bytes = idx_bytes(&[1], &[0, 0, 0, 0]);
+    bytes[2] = 0x0D;
+    let path = write_file("float", &bytes);
+    assert!(read_idx(&path).is_err());
+}
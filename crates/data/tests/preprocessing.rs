@@ -0,0 +1,106 @@
+use data::*;
+use ndarray::{array, Array2};
+use polars::prelude::*;
+
+type Load<T> = fn(&str, &str) -> Result<T, Box<dyn std::error::Error>>;
+
+fn save_dir(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("data-preprocessing-test-{}-{}", std::process::id(), name));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.to_str().unwrap().to_string()
+}
+
+fn features() -> Array2<f32> {
+    array![[1.0, 10.0], [2.0, f32::NAN], [3.0, 30.0], [6.0, 40.0]]
+}
+
+// NaN in the same places and equal values elsewhere
+fn assert_same(a: &Array2<f32>, b: &Array2<f32>) {
+    assert_eq!(a.dim(), b.dim());
+    for (x, y) in a.iter().zip(b.iter()) {
+        assert!((x.is_nan() && y.is_nan()) || (x - y).abs() < 1e-6, "{} != {}", x, y);
+    }
+}
+
+fn round_trip<T: Transform>(mut fitted: T, load: Load<T>, name: &str) {
+    let dir = save_dir(name);
+    let expected = fitted.fit_transform(&features());
+    fitted.save(&dir, name).unwrap();
+    let loaded = load(&dir, name).unwrap();
+    assert_same(&loaded.transform(&features()), &expected);
+}
+
+#[test]
+fn standard_scaler_statistics() {
+    let mut scaler = StandardScaler::new();
+    scaler.fit(&features());
+    assert_eq!(scaler.mean, array![3.0, 80.0 / 3.0]);
+    assert!((scaler.std[0] - 3.5_f32.sqrt()).abs() < 1e-6);
+    // NaN passes through
+    assert!(scaler.transform(&features())[[1, 1]].is_nan());
+}
+
+#[test]
+fn min_max_scaler_to_unit_range() {
+    let scaled = MinMaxScaler::new().fit_transform(&features());
+    assert_same(&scaled, &array![[0.0, 0.0], [0.2, f32::NAN], [0.4, 2.0 / 3.0], [1.0, 1.0]]);
+}
+
+#[test]
+fn imputer_fills_median() {
+    let filled = SimpleImputer::new(Imputation::Median).fit_transform(&features());
+    assert_eq!(filled[[1, 1]], 30.0);
+    assert_eq!(filled[[0, 0]], 1.0);
+}
+
+#[test]
+fn scalers_round_trip_through_save_and_load() {
+    round_trip(StandardScaler::new(), StandardScaler::load, "standard");
+    round_trip(MinMaxScaler::new(), MinMaxScaler::load, "minmax");
+    round_trip(RobustScaler::new(), RobustScaler::load, "robust");
+    round_trip(SimpleImputer::new(Imputation::Mean), SimpleImputer::load, "imputer");
+}
+
+#[test]
+fn min_max_scaler_leaves_empty_columns_unscaled() {
+    let x = array![[1.0, f32::NAN], [3.0, f32::NAN]];
+    let mut scaler = MinMaxScaler::new();
+    scaler.fit(&x);
+    assert_eq!((scaler.min[1], scaler.max[1]), (0.0, 1.0));
+    // values that only show up after fitting pass through
+    assert_same(&scaler.transform(&array![[2.0, 5.0], [1.0, f32::NAN]]), &array![[0.5, 5.0], [0.0, f32::NAN]]);
+}
+
+#[test]
+fn one_hot_encoder_round_trips_through_save_and_load() {
+    let df = df!("color" => ["red", "blue", "red"], "size" => ["s", "m", "l"]).unwrap();
+    let mut encoder = OneHotEncoder::new();
+    let encoded = encoder.fit_transform(&df, &["color", "size"]).unwrap();
+    // categories are sorted: blue, red then l, m, s
+    assert_eq!(encoded, array![[0.0, 1.0, 0.0, 0.0, 1.0], [1.0, 0.0, 0.0, 1.0, 0.0], [0.0, 1.0, 1.0, 0.0, 0.0]]);
+
+    let dir = save_dir("onehot");
+    encoder.save(&dir, "onehot").unwrap();
+    let loaded = OneHotEncoder::load(&dir, "onehot").unwrap();
+    assert_eq!(loaded.categories, encoder.categories);
+
+    // unseen categories are all zeros
+    let unseen = df!("color" => ["green"], "size" => ["m"]).unwrap();
+    assert_eq!(loaded.transform(&unseen).unwrap(), array![[0.0, 0.0, 0.0, 1.0, 0.0]]);
+}
+
+#[test]
+fn one_hot_encoder_keeps_columns_without_categories() {
+    let missing: [Option<&str>; 3] = [None, None, None];
+    let df = df!("empty" => missing, "color" => ["red", "", "red"], "none" => missing).unwrap();
+    let mut encoder = OneHotEncoder::new();
+    let encoded = encoder.fit_transform(&df, &["empty", "color", "none"]).unwrap();
+    assert_eq!(encoder.categories[0], ("empty".to_string(), vec![]));
+    assert_eq!(encoded.ncols(), 2);
+
+    let dir = save_dir("onehot-empty");
+    encoder.save(&dir, "onehot").unwrap();
+    let loaded = OneHotEncoder::load(&dir, "onehot").unwrap();
+    assert_eq!(loaded.categories, encoder.categories);
+    assert_eq!(loaded.transform(&df).unwrap(), encoded);
+}
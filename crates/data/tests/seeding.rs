@@ -0,0 +1,45 @@
+use data::*;
+use ndarray::Array2;
+
+fn order(seed: u64, epoch: u64) -> Vec<f32> {
+    let features = Array2::from_shape_fn((32, 1), |(i, _)| i as f32);
+    let mut loader = DataLoader::new(ArrayDataset::new(features.clone(), features), 32);
+    loader.shuffle = Some(seed);
+    loader.epoch = epoch;
+    let (features, _) = loader.iter().next().unwrap();
+    features.column(0).to_vec()
+}
+
+fn noise(seed: u64, epoch: u64) -> Vec<f32> {
+    let augmenter = Augmenter::new(2, 2, vec![Augmentation::GaussianNoise { std: 1.0 }], seed).unwrap();
+    augmenter.for_epoch(epoch).apply(&Array2::zeros((1, 4))).row(0).to_vec()
+}
+
+#[test]
+fn shuffle_is_reproducible() {
+    assert_eq!(order(7, 3), order(7, 3));
+    assert_ne!(order(7, 3), order(7, 4));
+}
+
+#[test]
+fn shuffle_of_next_epoch_is_not_next_seed() {
+    assert_ne!(order(5, 1), order(6, 0));
+    assert_ne!(order(0, 1), order(1, 0));
+}
+
+#[test]
+fn augmentation_of_next_epoch_is_not_next_seed() {
+    assert_eq!(noise(5, 1), noise(5, 1));
+    assert_ne!(noise(5, 1), noise(6, 0));
+}
+
+#[test]
+fn for_epoch_keeps_base_seed() {
+    let augmenter = Augmenter::new(2, 2, vec![Augmentation::GaussianNoise { std: 1.0 }], 9).unwrap();
+    let epoch = augmenter.for_epoch(4);
+    assert_eq!(epoch.seed, 9);
+    assert_eq!(
+        epoch.for_epoch(2).apply(&Array2::zeros((1, 4))),
+        augmenter.for_epoch(2).apply(&Array2::zeros((1, 4)))
+    );
+}
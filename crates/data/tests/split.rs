@@ -0,0 +1,79 @@
+use std::collections::BTreeSet;
+
+use data::*;
+use ndarray::Array2;
+
+// 20 rows, labels 0 for 12 rows and 1 for 8, the feature is the row index
+fn dataset() -> (Array2<f32>, Array2<f32>) {
+    let x = Array2::from_shape_fn((20, 1), |(i, _)| i as f32);
+    let y = Array2::from_shape_fn((20, 1), |(i, _)| if i < 12 { 0.0 } else { 1.0 });
+    (x, y)
+}
+
+fn count(y: &Array2<f32>, label: f32) -> usize {
+    y.iter().filter(|&&l| l == label).count()
+}
+
+fn rows(x: &Array2<f32>) -> BTreeSet<usize> {
+    x.iter().map(|&v| v as usize).collect()
+}
+
+#[test]
+fn stratified_split_keeps_label_shares() {
+    let (x, y) = dataset();
+    let ((train_x, train_y), (test_x, test_y)) = train_test_split(&x, &y, 0.25, 3, true);
+    assert_eq!((count(&test_y, 0.0), count(&test_y, 1.0)), (3, 2));
+    assert_eq!((count(&train_y, 0.0), count(&train_y, 1.0)), (9, 6));
+
+    // rows stay paired with their labels and are used once
+    for (x, y) in train_x.iter().chain(test_x.iter()).zip(train_y.iter().chain(test_y.iter())) {
+        assert_eq!(*y, if *x < 12.0 { 0.0 } else { 1.0 });
+    }
+    let all: BTreeSet<usize> = rows(&train_x).union(&rows(&test_x)).copied().collect();
+    assert_eq!(all, (0..20).collect());
+}
+
+#[test]
+fn split_is_reproducible() {
+    let (x, y) = dataset();
+    let (a, _) = train_test_split(&x, &y, 0.25, 3, false);
+    let (b, _) = train_test_split(&x, &y, 0.25, 3, false);
+    let (c, _) = train_test_split(&x, &y, 0.25, 4, false);
+    assert_eq!(a.0, b.0);
+    assert_ne!(a.0, c.0);
+}
+
+#[test]
+fn stratified_three_way_split() {
+    let (x, y) = dataset();
+    let ((_, train_y), (_, val_y), (_, test_y)) = train_val_test_split(&x, &y, 0.25, 0.25, 1, true);
+    assert_eq!((count(&train_y, 0.0), count(&train_y, 1.0)), (6, 4));
+    assert_eq!((count(&val_y, 0.0), count(&val_y, 1.0)), (3, 2));
+    assert_eq!((count(&test_y, 0.0), count(&test_y, 1.0)), (3, 2));
+}
+
+#[test]
+fn stratified_folds_spread_every_label() {
+    let (_, y) = dataset();
+    let folds = stratified_k_fold(&y, 4, 7);
+    assert_eq!(folds.len(), 4);
+
+    let mut seen = BTreeSet::new();
+    for (train, val) in folds.iter() {
+        assert_eq!(train.len() + val.len(), 20);
+        let zeros = val.iter().filter(|&&row| row < 12).count();
+        assert_eq!((zeros, val.len() - zeros), (3, 2));
+        assert!(val.iter().all(|row| !train.contains(row)));
+        assert!(val.iter().all(|&row| seen.insert(row)));
+    }
+    assert_eq!(seen.len(), 20);
+}
+
+#[test]
+fn k_fold_covers_every_row_once() {
+    let folds = k_fold(10, 3, 0);
+    let sizes: Vec<usize> = folds.iter().map(|(_, val)| val.len()).collect();
+    assert_eq!(sizes, vec![4, 3, 3]);
+    let all: BTreeSet<usize> = folds.iter().flat_map(|(_, val)| val.iter().copied()).collect();
+    assert_eq!(all, (0..10).collect());
+}
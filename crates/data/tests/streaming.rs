@@ -0,0 +1,143 @@
+use std::fs::File;
+
+use data::*;
+use polars::prelude::*;
+
+fn temp_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("data-streaming-test-{}-{}", std::process::id(), name));
+    path.to_str().unwrap().to_string()
+}
+
+// row i has features (i, 2i) and label i % 2
+fn write_csv(name: &str, rows: usize) -> String {
+    let mut csv = String::from("a,b,label\n");
+    for i in 0..rows {
+        csv.push_str(&format!("{},{},{}\n", i, 2 * i, i % 2));
+    }
+    let path = temp_path(name);
+    std::fs::write(&path, csv).unwrap();
+    path
+}
+
+fn write_parquet(name: &str, rows: usize) -> String {
+    let ids: Vec<f32> = (0..rows).map(|i| i as f32).collect();
+    let mut df = df!(
+        "a" => &ids,
+        "b" => ids.iter().map(|i| 2.0 * i).collect::<Vec<f32>>(),
+        "label" => (0..rows).map(|i| (i % 2) as i64).collect::<Vec<i64>>(),
+    ).unwrap();
+    let path = temp_path(name);
+    ParquetWriter::new(File::create(&path).unwrap()).finish(&mut df).unwrap();
+    path
+}
+
+fn loader(path: &str, format: FileFormat) -> StreamingLoader {
+    let mut loader = StreamingLoader::new(path, format, "label", 4);
+    // batches of 4 straddle Parquet chunks of 3 rows, CSV takes the size as a hint only
+    loader.chunk_rows = 3;
+    loader
+}
+
+// first feature of every row, in the order batches hand them out
+fn row_ids(loader: &mut StreamingLoader) -> Vec<usize> {
+    loader
+        .iter()
+        .unwrap()
+        .flat_map(|batch| batch.unwrap().0.column(0).to_vec())
+        .map(|a| a as usize)
+        .collect()
+}
+
+fn batch_sizes(loader: &mut StreamingLoader) -> Vec<usize> {
+    loader.iter().unwrap().map(|batch| batch.unwrap().0.nrows()).collect()
+}
+
+#[test]
+fn batches_keep_file_order_across_chunks() {
+    let path = write_parquet("order.parquet", 10);
+    let mut loader = loader(&path, FileFormat::Parquet);
+    let batches: Vec<Batch> = loader.iter().unwrap().map(Result::unwrap).collect();
+
+    assert_eq!(batches.iter().map(|(x, _)| x.nrows()).collect::<Vec<_>>(), vec![4, 4, 2]);
+    let (features, labels) = &batches[1];
+    assert_eq!(features.row(0).to_vec(), vec![4.0, 8.0]);
+    assert_eq!(labels.column(0).to_vec(), vec![0.0, 1.0, 0.0, 1.0]);
+    assert_eq!(row_ids(&mut loader), (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn parquet_batches_match_csv() {
+    let mut csv = loader(&write_csv("same.csv", 10), FileFormat::Csv);
+    let mut parquet = loader(&write_parquet("same.parquet", 10), FileFormat::Parquet);
+    let csv: Vec<Batch> = csv.iter().unwrap().map(Result::unwrap).collect();
+    let parquet: Vec<Batch> = parquet.iter().unwrap().map(Result::unwrap).collect();
+    assert_eq!(csv, parquet);
+}
+
+#[test]
+fn drop_last_skips_the_partial_batch() {
+    let mut loader = loader(&write_csv("drop.csv", 10), FileFormat::Csv);
+    loader.drop_last = true;
+    assert_eq!(batch_sizes(&mut loader), vec![4, 4]);
+}
+
+#[test]
+fn feature_columns_are_selected() {
+    let mut loader = loader(&write_csv("select.csv", 5), FileFormat::Csv);
+    loader.feature_cols = Some(vec!["b".to_string()]);
+    let (features, _) = loader.iter().unwrap().next().unwrap().unwrap();
+    assert_eq!(features.column(0).to_vec(), vec![0.0, 2.0, 4.0, 6.0]);
+    assert_eq!(features.ncols(), 1);
+}
+
+#[test]
+fn shuffle_buffer_is_reproducible_per_epoch() {
+    let path = write_parquet("shuffle.parquet", 20);
+    let shuffled = |seed| {
+        let mut loader = loader(&path, FileFormat::Parquet);
+        loader.shuffle_buffer = 8;
+        loader.seed = seed;
+        (row_ids(&mut loader), row_ids(&mut loader))
+    };
+
+    let (first, second) = shuffled(3);
+    assert_eq!(shuffled(3), (first.clone(), second.clone()));
+    assert_ne!(first, second);
+    assert_ne!(first, (0..20).collect::<Vec<_>>());
+
+    let mut sorted = first.clone();
+    sorted.sort();
+    assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+}
+
+#[test]
+fn missing_cells_are_nan() {
+    let path = temp_path("missing.csv");
+    std::fs::write(&path, "a,b,label\n1,,0\n2,3,1\n").unwrap();
+    let (features, _) = loader(&path, FileFormat::Csv).iter().unwrap().next().unwrap().unwrap();
+    assert!(features[[0, 1]].is_nan());
+    assert_eq!(features[[1, 1]], 3.0);
+}
+
+#[test]
+fn malformed_cells_name_row_and_column() {
+    // numbers stored as text, row 7 in the third chunk isn't one
+    let b: Vec<String> = (0..10).map(|i| if i == 7 { "oops".to_string() } else { i.to_string() }).collect();
+    let mut df = df!("a" => (0..10).map(|i| i as f32).collect::<Vec<f32>>(), "b" => b, "label" => vec![0i64; 10]).unwrap();
+    let path = temp_path("malformed.parquet");
+    ParquetWriter::new(File::create(&path).unwrap()).finish(&mut df).unwrap();
+
+    let mut loader = loader(&path, FileFormat::Parquet);
+    let mut batches = loader.iter().unwrap();
+    assert!(batches.next().unwrap().is_ok());
+    let err = batches.next().unwrap().unwrap_err().to_string();
+    assert!(err.contains("column b, row 7"), "{}", err);
+}
+
+#[test]
+fn malformed_csv_cells_are_errors() {
+    let csv = temp_path("malformed.csv");
+    std::fs::write(&csv, "a,b,label\n1,2,0\n3,x4,1\n").unwrap();
+    let err = loader(&csv, FileFormat::Csv).iter().unwrap().next().unwrap().unwrap_err().to_string();
+    assert!(err.contains("column b, row 1"), "{}", err);
+}
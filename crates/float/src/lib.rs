@@ -0,0 +1,170 @@
+use std::fmt::{Debug, Display};
+use std::ops::{AddAssign, DivAssign, MulAssign, SubAssign};
+
+use ndarray::{Array1, Array2, ArrayBase, Axis, Data, Ix2, LinalgScalar};
+
+pub use half::{bf16, f16};
+// math on `Float::Accum` needs `num_traits::Float` in scope
+pub use num_traits;
+
+// Element type of layers, losses and optimizers: f32 (default), f64, f16 or bf16.
+// Matrix products and sums accumulate in `Accum`, so half precision models store and multiply
+// in 16 bits but add up dot products in f32. Hyperparameters (learning rates, lambdas) stay f32.
+pub trait Float:
+    num_traits::Float
+    + LinalgScalar
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + Debug
+    + Display
+    + Default
+    + Send
+    + Sync
+    + 'static
+{
+    type Accum: Float;
+
+    // e.g. "f32", shown by `summary`
+    const NAME: &'static str;
+
+    fn from_f32(x: f32) -> Self;
+    fn into_f32(self) -> f32;
+    fn from_f64(x: f64) -> Self;
+    fn into_f64(self) -> f64;
+
+    fn to_accum(self) -> Self::Accum;
+    fn from_accum(x: Self::Accum) -> Self;
+
+    // a · b with products summed in `Accum`
+    fn matmul<S1, S2>(a: &ArrayBase<S1, Ix2>, b: &ArrayBase<S2, Ix2>) -> Array2<Self>
+    where
+        S1: Data<Elem = Self>,
+        S2: Data<Elem = Self>,
+    {
+        a.dot(b)
+    }
+
+    // sum along `axis` in `Accum`
+    fn sum_axis<S: Data<Elem = Self>>(a: &ArrayBase<S, Ix2>, axis: Axis) -> Array1<Self> {
+        a.sum_axis(axis)
+    }
+
+    // sum of all values in `Accum`
+    fn sum_all<S: Data<Elem = Self>>(a: &ArrayBase<S, Ix2>) -> Self {
+        a.sum()
+    }
+}
+
+impl Float for f32 {
+    type Accum = f32;
+    const NAME: &'static str = "f32";
+
+    fn from_f32(x: f32) -> Self {
+        x
+    }
+
+    fn into_f32(self) -> f32 {
+        self
+    }
+
+    fn from_f64(x: f64) -> Self {
+        x as f32
+    }
+
+    fn into_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn to_accum(self) -> f32 {
+        self
+    }
+
+    fn from_accum(x: f32) -> Self {
+        x
+    }
+}
+
+impl Float for f64 {
+    type Accum = f64;
+    const NAME: &'static str = "f64";
+
+    fn from_f32(x: f32) -> Self {
+        x as f64
+    }
+
+    fn into_f32(self) -> f32 {
+        self as f32
+    }
+
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+
+    fn into_f64(self) -> f64 {
+        self
+    }
+
+    fn to_accum(self) -> f64 {
+        self
+    }
+
+    fn from_accum(x: f64) -> Self {
+        x
+    }
+}
+
+// 16 bit types widen to f32 for products and sums and round the result back
+macro_rules! half_float {
+    ($t:ty, $name:literal) => {
+        impl Float for $t {
+            type Accum = f32;
+            const NAME: &'static str = $name;
+
+            fn from_f32(x: f32) -> Self {
+                <$t>::from_f32(x)
+            }
+
+            fn into_f32(self) -> f32 {
+                self.to_f32()
+            }
+
+            fn from_f64(x: f64) -> Self {
+                <$t>::from_f64(x)
+            }
+
+            fn into_f64(self) -> f64 {
+                self.to_f64()
+            }
+
+            fn to_accum(self) -> f32 {
+                self.to_f32()
+            }
+
+            fn from_accum(x: f32) -> Self {
+                <$t>::from_f32(x)
+            }
+
+            fn matmul<S1, S2>(a: &ArrayBase<S1, Ix2>, b: &ArrayBase<S2, Ix2>) -> Array2<Self>
+            where
+                S1: Data<Elem = Self>,
+                S2: Data<Elem = Self>,
+            {
+                let product = a.mapv(|x| x.to_f32()).dot(&b.mapv(|x| x.to_f32()));
+                product.mapv(<$t>::from_f32)
+            }
+
+            fn sum_axis<S: Data<Elem = Self>>(a: &ArrayBase<S, Ix2>, axis: Axis) -> Array1<Self> {
+                a.map_axis(axis, |lane| <$t>::from_f32(lane.iter().map(|x| x.to_f32()).sum()))
+            }
+
+            fn sum_all<S: Data<Elem = Self>>(a: &ArrayBase<S, Ix2>) -> Self {
+                <$t>::from_f32(a.iter().map(|x| x.to_f32()).sum())
+            }
+        }
+    };
+}
+
+half_float!(f16, "f16");
+half_float!(bf16, "bf16");
@@ -0,0 +1,170 @@
+use std::any::Any;
+use std::error::Error;
+use std::sync::Arc;
+
+use autograd::{Tape, Var};
+use ndarray::{Array1, Array2, Axis};
+use super::{Float, ParamsGrads, Propagate, Regularization};
+
+// (input, weights, bias as a 1 x n row) -> output, recorded on one tape of `T`.
+// Blocks with several parameters (LSTM gates, attention Q/K/V) pack them into the weights and slice them apart.
+pub type BlockFn<T = f32> = Arc<dyn for<'t> Fn(Var<'t, T>, Var<'t, T>, Var<'t, T>) -> Var<'t, T> + Send + Sync>;
+
+// Layer whose backward pass is derived from its forward ops, e.g. a dense tanh layer:
+// AutogradLayer::new(5, w, b, Arc::new(|x, w, b| (x.matmul(w) + b).tanh()))
+// The tape computes in `F::Accum`: f64 for f64 layers, f32 for f32, f16 and bf16 layers.
+pub struct AutogradLayer<F: Float = f32> {
+    input_dim: usize,
+    weights: Array2<F>,
+    bias: Array1<F>,
+    grad_weights: Array2<F>,
+    grad_bias: Array1<F>,
+    input: Option<Array2<F>>,
+    block: BlockFn<F::Accum>,
+    regularization: Regularization,
+    frozen: bool,
+    accumulate: bool,
+}
+
+impl<F: Float> AutogradLayer<F> {
+    pub fn new(input_dim: usize, weights: Array2<F>, bias: Array1<F>, block: BlockFn<F::Accum>) -> Self {
+        let grad_weights = Array2::zeros(weights.dim());
+        let grad_bias = Array1::zeros(bias.len());
+        AutogradLayer {
+            input_dim,
+            weights,
+            bias,
+            grad_weights,
+            grad_bias,
+            input: None,
+            block,
+            regularization: Regularization::None,
+            frozen: false,
+            accumulate: false,
+        }
+    }
+
+    // Penalty on the weights, its gradient is added in `backward` like for `Layer`
+    pub fn with_regularization(mut self, regularization: Regularization) -> Self {
+        self.regularization = regularization;
+        self
+    }
+
+    // Same layer in another precision, without gradients or cache.
+    // The block is kept, so `G` must compute in the same type, e.g. f32 to f16 but not f32 to f64.
+    pub fn cast<G: Float>(&self) -> Result<AutogradLayer<G>, Box<dyn Error>> {
+        let block: Box<dyn Any> = Box::new(self.block.clone());
+        let Ok(block) = block.downcast::<BlockFn<G::Accum>>() else {
+            return Err(format!(
+                "the block of an {} autograd layer computes in {} and can't be cast to {}, build the layer again with a block for {}",
+                F::NAME, <F::Accum as Float>::NAME, G::NAME, <G::Accum as Float>::NAME
+            ).into());
+        };
+        let weights = self.weights.mapv(|w| G::from_f64(w.into_f64()));
+        let bias = self.bias.mapv(|b| G::from_f64(b.into_f64()));
+        let mut layer = AutogradLayer::new(self.input_dim, weights, bias, *block);
+        layer.regularization = self.regularization;
+        layer.frozen = self.frozen;
+        layer.accumulate = self.accumulate;
+        Ok(layer)
+    }
+
+    // Copy for a data-parallel worker, see `Layer::replica`
+    pub fn replica(&self) -> Self {
+        let mut layer = AutogradLayer::new(self.input_dim, self.weights.clone(), self.bias.clone(), self.block.clone());
+        layer.frozen = self.frozen;
+        layer
+    }
+
+    pub fn input_dim(&self) -> usize {
+        self.input_dim
+    }
+
+    // width of the block's output, found by running it on one row of zeros
+    pub fn output_dim(&self) -> usize {
+        self.infer(&Array2::zeros((1, self.input_dim))).ncols()
+    }
+
+    pub fn num_params(&self) -> usize {
+        self.weights.len() + self.bias.len()
+    }
+
+    pub fn regularization(&self) -> &Regularization {
+        &self.regularization
+    }
+
+    fn bias_row(&self) -> Array2<F::Accum> {
+        self.bias.mapv(F::to_accum).insert_axis(Axis(0))
+    }
+}
+
+impl<F: Float> Propagate<F> for AutogradLayer<F> {
+    fn forward(&mut self, input: &Array2<F>) -> Array2<F> {
+        self.input = Some(input.clone());
+        self.infer(input)
+    }
+
+    fn infer(&self, input: &Array2<F>) -> Array2<F> {
+        let tape = Tape::new();
+        let (x, w, b) = (tape.var(input.mapv(F::to_accum)), tape.var(self.weights.mapv(F::to_accum)), tape.var(self.bias_row()));
+        (self.block)(x, w, b).value().mapv(F::from_accum)
+    }
+
+    fn backward(&mut self, grad_output: &Array2<F>) -> Array2<F> {
+        let input = self.input.as_ref().expect("No cache");
+        // the tape only lives for this pass, forward stays as cheap as `infer`
+        let tape = Tape::new();
+        let (x, w, b) = (tape.var(input.mapv(F::to_accum)), tape.var(self.weights.mapv(F::to_accum)), tape.var(self.bias_row()));
+        let grads = (self.block)(x, w, b).backward_with(&grad_output.mapv(F::to_accum));
+        let grad = |var: Var<F::Accum>, dim: (usize, usize)| grads.wrt(var).map_or_else(|| Array2::zeros(dim), |g| g.mapv(F::from_accum));
+        let grad_input = grad(x, input.dim());
+
+        if self.frozen {
+            return grad_input;
+        }
+
+        let mut grad_w = grad(w, self.weights.dim());
+        let grad_b = grad(b, (1, self.bias.len())).row(0).to_owned();
+        if let Some(grad_penalty) = self.regularization.gradient(&self.weights) {
+            grad_w += &grad_penalty;
+        }
+
+        if self.accumulate {
+            self.grad_weights += &grad_w;
+            self.grad_bias += &grad_b;
+        } else {
+            self.grad_weights = grad_w;
+            self.grad_bias = grad_b;
+        }
+        grad_input
+    }
+
+    fn params_grads(&mut self) -> Option<ParamsGrads<'_, F>> {
+        Some((&mut self.weights, &mut self.grad_weights, &mut self.bias, &mut self.grad_bias))
+    }
+
+    fn penalty(&self) -> F {
+        self.regularization.penalty(&self.weights)
+    }
+
+    fn set_frozen(&mut self, frozen: bool) {
+        self.frozen = frozen;
+    }
+
+    fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    fn set_accumulate(&mut self, accumulate: bool) {
+        self.accumulate = accumulate;
+    }
+
+    fn is_accumulating(&self) -> bool {
+        self.accumulate
+    }
+
+    fn zero_grad(&mut self) {
+        self.grad_weights.fill(F::zero());
+        self.grad_bias.fill(F::zero());
+    }
+}
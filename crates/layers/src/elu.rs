@@ -1,33 +1,44 @@
 use ndarray::Array2;
-use super::Propagate;
+use super::{Float, Propagate};
 
-pub struct ELU {
+pub struct ELU<F = f32> {
     alpha: f32,
-    cache: Option<Array2<f32>>,
+    cache: Option<Array2<F>>,
 }
 
-impl ELU {
+impl<F: Float> ELU<F> {
     pub fn new(alpha: f32) -> Self {
         ELU {
             alpha,
             cache: None,
         }
     }
+
+    // Same activation in another precision, without cache
+    pub fn cast<G: Float>(&self) -> ELU<G> {
+        ELU::new(self.alpha)
+    }
 }
 
-impl Propagate for ELU {
-    fn forward(&mut self, input: &Array2<f32>) -> Array2<f32> {
+impl<F: Float> Propagate<F> for ELU<F> {
+    fn forward(&mut self, input: &Array2<F>) -> Array2<F> {
         self.cache = Some(input.clone());
-        input.mapv(|x| if x > 0.0 { x } else { self.alpha * (x.exp() - 1.0) })
+        self.infer(input)
+    }
+
+    fn infer(&self, input: &Array2<F>) -> Array2<F> {
+        let alpha = F::from_f32(self.alpha);
+        input.mapv(|x| if x > F::zero() { x } else { alpha * (x.exp() - F::one()) })
     }
 
-    fn backward(&mut self, grad_output: &Array2<f32>) -> Array2<f32> {
+    fn backward(&mut self, grad_output: &Array2<F>) -> Array2<F> {
         let input = self.cache.as_ref().expect("No cache");
+        let alpha = F::from_f32(self.alpha);
         let grad = input.mapv(|x| {
-            if x > 0.0 {
-                1.0
+            if x > F::zero() {
+                F::one()
             } else {
-                self.alpha * x.exp()
+                alpha * x.exp()
             }
         });
         grad_output * grad
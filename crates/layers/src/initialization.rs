@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use ndarray::Array2;
+use ndarray_rand::rand::{Rng, RngCore};
+use ndarray_rand::rand_distr::{Distribution, Normal, Uniform};
+use ndarray_rand::RandomExt;
+
+// (fan_in, fan_out), generator -> array of shape (fan_in, fan_out)
+pub type InitFn = Arc<dyn Fn((usize, usize), &mut dyn RngCore) -> Array2<f32> + Send + Sync>;
+
+#[derive(Clone)]
+pub enum Initialization {
+    // normal with std sqrt(2 / fan_in)
+    He,
+    // uniform in ±sqrt(6 / fan_in)
+    HeUniform,
+    // normal with std sqrt(2 / (fan_in + fan_out))
+    Glorot,
+    // uniform in ±sqrt(6 / (fan_in + fan_out))
+    GlorotUniform,
+    // normal with std sqrt(1 / fan_in)
+    LeCun,
+    // uniform in ±sqrt(3 / fan_in)
+    LeCunUniform,
+    // normal with `std`, values further than 2 std from 0 are drawn again
+    TruncatedNormal { std: f32 },
+    // orthonormal rows or columns scaled by `gain`
+    Orthogonal { gain: f32 },
+    Constant(f32),
+    Zeros,
+    Custom(InitFn),
+}
+
+impl Initialization {
+    // Array of `shape`, fans are taken from the layer so bias can share the weights' scale
+    pub fn sample<R: Rng>(&self, shape: (usize, usize), fan_in: usize, fan_out: usize, rng: &mut R) -> Array2<f32> {
+        let (fan_in, fan_out) = (fan_in as f32, fan_out as f32);
+        match self {
+            Initialization::He => normal(shape, (2.0 / fan_in).sqrt(), rng),
+            Initialization::HeUniform => uniform(shape, (6.0 / fan_in).sqrt(), rng),
+            Initialization::Glorot => normal(shape, (2.0 / (fan_in + fan_out)).sqrt(), rng),
+            Initialization::GlorotUniform => uniform(shape, (6.0 / (fan_in + fan_out)).sqrt(), rng),
+            Initialization::LeCun => normal(shape, (1.0 / fan_in).sqrt(), rng),
+            Initialization::LeCunUniform => uniform(shape, (3.0 / fan_in).sqrt(), rng),
+            Initialization::TruncatedNormal { std } => {
+                let distr = Normal::new(0.0, *std).expect("Stddev for initialization must not be negative");
+                Array2::from_shape_simple_fn(shape, || loop {
+                    let x: f32 = distr.sample(rng);
+                    if x.abs() <= 2.0 * std {
+                        break x;
+                    }
+                })
+            }
+            Initialization::Orthogonal { gain } => orthogonal(shape, rng) * *gain,
+            Initialization::Constant(value) => Array2::from_elem(shape, *value),
+            Initialization::Zeros => Array2::zeros(shape),
+            Initialization::Custom(init) => {
+                let values = init(shape, rng);
+                assert_eq!(values.dim(), shape, "Custom initializer returned the wrong shape");
+                values
+            }
+        }
+    }
+}
+
+fn normal<R: Rng>(shape: (usize, usize), std: f32, rng: &mut R) -> Array2<f32> {
+    let distr = Normal::new(0.0, std).expect("Stddev for initialization must be positive");
+    Array2::random_using(shape, distr, rng)
+}
+
+fn uniform<R: Rng>(shape: (usize, usize), limit: f32, rng: &mut R) -> Array2<f32> {
+    Array2::random_using(shape, Uniform::new_inclusive(-limit, limit), rng)
+}
+
+// Gram-Schmidt on a gaussian matrix, orthonormal along the shorter dimension
+fn orthogonal<R: Rng>(shape: (usize, usize), rng: &mut R) -> Array2<f32> {
+    let (rows, cols) = shape;
+    let (long, short) = (rows.max(cols), rows.min(cols));
+    let mut q = normal((long, short), 1.0, rng);
+    for j in 0..short {
+        for k in 0..j {
+            let projection = q.column(j).dot(&q.column(k));
+            let previous = q.column(k).to_owned();
+            q.column_mut(j).scaled_add(-projection, &previous);
+        }
+        let norm = q.column(j).dot(&q.column(j)).sqrt();
+        q.column_mut(j).mapv_inplace(|x| x / norm);
+    }
+    if rows >= cols { q } else { q.reversed_axes().as_standard_layout().to_owned() }
+}
@@ -1,39 +1,32 @@
 use ndarray::{Array1, Array2, Axis};
-use ndarray_rand::{rand_distr, RandomExt};
-use super::Propagate;
+use ndarray_rand::rand::Rng;
+use super::{Float, ParamsGrads, Propagate, with_rng};
+pub use super::initialization::Initialization;
+pub use super::regularization::{Constraint, Regularization};
 
-pub enum Initialization {
-    He,
-    Glorot,
-    LeCun
-}
-
-pub enum Regularization {
-    None,
-    L1{lambda: f32},
-    L2{lambda: f32},
-    ElasticNet { l1: f32, l2: f32 },
-}
-
-pub struct Layer {
-    weights: Array2<f32>, // shape (input_dim, output_dim)
-    bias: Array1<f32>,    // shape (output_dim)
-    grad_weights: Array2<f32>,
-    grad_bias: Array1<f32>,
-    input: Option<Array2<f32>>,
+pub struct Layer<F = f32> {
+    weights: Array2<F>, // shape (input_dim, output_dim)
+    bias: Array1<F>,    // shape (output_dim)
+    grad_weights: Array2<F>,
+    grad_bias: Array1<F>,
+    input: Option<Array2<F>>,
     regularization: Regularization,
+    constraint: Constraint,
+    frozen: bool,
+    accumulate: bool,
 }
 
 
-impl Layer {
+impl<F: Float> Layer<F> {
+    // Weights are drawn from the seeded generator of the thread, see `set_seed`
     pub fn new(input_dim:usize, output_dim:usize, distribution: Initialization, regularization: Regularization) -> Self{
-        let stddev = match distribution {
-            Initialization::He => (2.0 / input_dim as f32).sqrt(),
-            Initialization::Glorot => (1.0 / (input_dim as f32 + output_dim as f32)).sqrt(),
-            Initialization::LeCun => (1.0 / (input_dim as f32)).sqrt(),
-        };
-        let distr = rand_distr::Normal::new(0.0, stddev).expect("Stddev for initialization must be positive");
-        let w = Array2::random((input_dim, output_dim), distr);
+        with_rng(|rng| Self::new_with_rng(input_dim, output_dim, distribution, regularization, rng))
+    }
+
+    // Bias starts at zero, see `with_bias`
+    pub fn new_with_rng<R: Rng>(input_dim:usize, output_dim:usize, distribution: Initialization, regularization: Regularization, rng: &mut R) -> Self{
+        // drawn in f32 whatever the element type, so one seed gives the same model in every precision
+        let w = distribution.sample((input_dim, output_dim), input_dim, output_dim, rng).mapv(F::from_f32);
         let b = Array1::zeros(output_dim);
         let gw = Array2::zeros((input_dim, output_dim));
         let gb = Array1::zeros(output_dim);
@@ -43,49 +36,149 @@ impl Layer {
             grad_weights: gw, 
             grad_bias: gb, 
             input: None, 
-            regularization
+            regularization,
+            constraint: Constraint::None,
+            frozen: false,
+            accumulate: false,
         }
     }
 
-    pub fn set_params(&mut self, weights: Array2<f32>, bias: Array1<f32>) {
+    // Draw bias from `distribution` with the layer's fans, e.g. `Initialization::Constant(0.01)` for ReLU layers
+    pub fn with_bias(mut self, distribution: Initialization) -> Self {
+        let (input_dim, output_dim) = self.weights.dim();
+        let bias = with_rng(|rng| distribution.sample((1, output_dim), input_dim, output_dim, rng));
+        self.bias = bias.row(0).mapv(F::from_f32);
+        self
+    }
+
+    // Constrain weights after every optimizer step
+    pub fn with_constraint(mut self, constraint: Constraint) -> Self {
+        self.constraint = constraint;
+        self
+    }
+
+    pub fn set_params(&mut self, weights: Array2<F>, bias: Array1<F>) {
         self.weights = weights;
         self.bias = bias;
     }
+
+    // Same layer in another precision, without gradients or cache
+    pub fn cast<G: Float>(&self) -> Layer<G> {
+        let (input_dim, output_dim) = self.weights.dim();
+        Layer {
+            weights: self.weights.mapv(|w| G::from_f64(w.into_f64())),
+            bias: self.bias.mapv(|b| G::from_f64(b.into_f64())),
+            grad_weights: Array2::zeros((input_dim, output_dim)),
+            grad_bias: Array1::zeros(output_dim),
+            input: None,
+            regularization: self.regularization,
+            constraint: self.constraint,
+            frozen: self.frozen,
+            accumulate: self.accumulate,
+        }
+    }
+
+    // Copy for a data-parallel worker: zero gradients, no cache and no regularization,
+    // whose gradient the original layer adds once for the whole batch
+    pub fn replica(&self) -> Self {
+        let (input_dim, output_dim) = self.weights.dim();
+        Layer {
+            weights: self.weights.clone(),
+            bias: self.bias.clone(),
+            grad_weights: Array2::zeros((input_dim, output_dim)),
+            grad_bias: Array1::zeros(output_dim),
+            input: None,
+            regularization: Regularization::None,
+            constraint: self.constraint,
+            frozen: self.frozen,
+            accumulate: false,
+        }
+    }
+
+    pub fn input_dim(&self) -> usize {
+        self.weights.nrows()
+    }
+
+    pub fn output_dim(&self) -> usize {
+        self.weights.ncols()
+    }
+
+    pub fn regularization(&self) -> &Regularization {
+        &self.regularization
+    }
+
+    pub fn constraint(&self) -> &Constraint {
+        &self.constraint
+    }
 }
-impl Propagate for Layer {
-    fn forward(&mut self, input:&Array2<f32>) -> Array2<f32> {
+impl<F: Float> Propagate<F> for Layer<F> {
+    fn forward(&mut self, input:&Array2<F>) -> Array2<F> {
         self.input = Some(input.clone());
-        input.dot(&self.weights) + &self.bias
+        self.infer(input)
+    }
+
+    fn infer(&self, input: &Array2<F>) -> Array2<F> {
+        F::matmul(input, &self.weights) + &self.bias
     }
 
-    fn backward(&mut self, grad_output: &Array2<f32>) -> Array2<f32> {
+    fn backward(&mut self, grad_output: &Array2<F>) -> Array2<F> {
+        // Frozen weights need no gradient, only propagate to inputs
+        if self.frozen {
+            return F::matmul(grad_output, &self.weights.t());
+        }
+
         let input = self.input.as_ref().expect("No cache");
         // Compute gradients
-        let mut grad_w = input.t().dot(grad_output);
-        let grad_b = grad_output.sum_axis(Axis(0));
-
-        match self.regularization {
-            Regularization::L1{lambda} => {
-                grad_w += &(self.weights.mapv(|x| x.signum()) * lambda);
-            }
-            Regularization::L2{lambda} => {
-                grad_w += &(self.weights.mapv(|w| 2.0 * lambda * w));
-            }
-            Regularization::ElasticNet { l1, l2 } => {
-                let l1_term = self.weights.mapv(|x| x.signum()) * l1;
-                let l2_term = self.weights.mapv(|w| 2.0 * l2 * w);
-                grad_w += &(l1_term + l2_term);
-            }
-            Regularization::None => {}
+        let mut grad_w = F::matmul(&input.t(), grad_output);
+        let grad_b = F::sum_axis(grad_output, Axis(0));
+
+        // derivative of the penalty this layer adds to the loss
+        if let Some(grad_penalty) = self.regularization.gradient(&self.weights) {
+            grad_w += &grad_penalty;
+        }
+
+
+        if self.accumulate {
+            self.grad_weights += &grad_w;
+            self.grad_bias += &grad_b;
+        } else {
+            self.grad_weights = grad_w;
+            self.grad_bias = grad_b;
         }
-        
-        self.grad_weights = grad_w;
-        self.grad_bias = grad_b;
         // Propagate gradient to inputs
-        grad_output.dot(&self.weights.t())
+        F::matmul(grad_output, &self.weights.t())
     }
 
-    fn params_grads(&mut self) -> Option<(&mut Array2<f32>, &mut Array2<f32>, &mut Array1<f32>, &mut Array1<f32>)> {
+    fn params_grads(&mut self) -> Option<ParamsGrads<'_, F>> {
         Some((&mut self.weights, &mut self.grad_weights, &mut self.bias ,&mut self.grad_bias))
     }
+
+    fn penalty(&self) -> F {
+        self.regularization.penalty(&self.weights)
+    }
+
+    fn apply_constraint(&mut self) {
+        self.constraint.apply(&mut self.weights);
+    }
+
+    fn set_frozen(&mut self, frozen: bool) {
+        self.frozen = frozen;
+    }
+
+    fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    fn set_accumulate(&mut self, accumulate: bool) {
+        self.accumulate = accumulate;
+    }
+
+    fn is_accumulating(&self) -> bool {
+        self.accumulate
+    }
+
+    fn zero_grad(&mut self) {
+        self.grad_weights.fill(F::zero());
+        self.grad_bias.fill(F::zero());
+    }
 }
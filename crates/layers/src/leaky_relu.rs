@@ -1,27 +1,38 @@
 use ndarray::Array2;
-use super::Propagate;
+use super::{Float, Propagate};
 
-pub struct LeakyReLu {
+pub struct LeakyReLu<F = f32> {
     alpha: f32,
-    cache: Option<Array2<f32>>,
+    cache: Option<Array2<F>>,
 }
-impl LeakyReLu {
+impl<F: Float> LeakyReLu<F> {
     pub fn new(alpha: f32) -> Self { 
         LeakyReLu { 
             alpha,
             cache: None 
         } 
     }
+
+    // Same activation in another precision, without cache
+    pub fn cast<G: Float>(&self) -> LeakyReLu<G> {
+        LeakyReLu::new(self.alpha)
+    }
 }
-impl Propagate for LeakyReLu {
-    fn forward(&mut self, input: &Array2<f32>) -> Array2<f32> {
+impl<F: Float> Propagate<F> for LeakyReLu<F> {
+    fn forward(&mut self, input: &Array2<F>) -> Array2<F> {
         self.cache = Some(input.clone());
-        input.mapv(|x| if x > 0.0 { x } else { self.alpha * x })
+        self.infer(input)
     }
 
-    fn backward(&mut self, grad_output: &Array2<f32>) -> Array2<f32> {
+    fn infer(&self, input: &Array2<F>) -> Array2<F> {
+        let alpha = F::from_f32(self.alpha);
+        input.mapv(|x| if x > F::zero() { x } else { alpha * x })
+    }
+
+    fn backward(&mut self, grad_output: &Array2<F>) -> Array2<F> {
         let input = self.cache.as_ref().expect("No cache");
-        let grad = input.mapv(|x| if x > 0.0 { 1.0 } else { self.alpha });
+        let alpha = F::from_f32(self.alpha);
+        let grad = input.mapv(|x| if x > F::zero() { F::one() } else { alpha });
         grad_output * grad
     }
-}
\ No newline at end of file
+}
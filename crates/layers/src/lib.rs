@@ -1,36 +1,122 @@
+use std::error::Error;
+
 use ndarray::{Array1, Array2};
-pub use layer::{Layer, Initialization, Regularization};
+pub use layer::{Layer, Initialization, Regularization, Constraint};
+pub use initialization::InitFn;
 pub use softmax::Softmax;
 pub use relu::ReLu;
 pub use leaky_relu::LeakyReLu;
 pub use elu::ELU;
 pub use selu::SELU;
+pub use seed::{set_seed, clear_seed, with_rng, next_seed};
+pub use autograd_layer::{AutogradLayer, BlockFn};
+pub use autograd::{Gradients, Tape, Var};
+pub use float::{Float, bf16, f16};
 
-pub enum LayerTypes {
-    Layer(Layer),
-    Softmax(Softmax),
-    ReLu(ReLu),
-    LeakyReLu(LeakyReLu),
-    ELU(ELU),
-    SELU(SELU),
+#[allow(clippy::large_enum_variant)]
+pub enum LayerTypes<F: Float = f32> {
+    Layer(Layer<F>),
+    Softmax(Softmax<F>),
+    ReLu(ReLu<F>),
+    LeakyReLu(LeakyReLu<F>),
+    ELU(ELU<F>),
+    SELU(SELU<F>),
+    Autograd(AutogradLayer<F>),
 }
 
-pub trait Propagate {
+impl<F: Float> LayerTypes<F> {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LayerTypes::Layer(_) => "Layer",
+            LayerTypes::Softmax(_) => "Softmax",
+            LayerTypes::ReLu(_) => "ReLu",
+            LayerTypes::LeakyReLu(_) => "LeakyReLu",
+            LayerTypes::ELU(_) => "ELU",
+            LayerTypes::SELU(_) => "SELU",
+            LayerTypes::Autograd(_) => "Autograd",
+        }
+    }
+
+    // Same layer in another precision, e.g. a trained f32 layer stored as f16 for inference
+    // Fails for autograd layers whose block computes in another type than `G` needs, see `AutogradLayer::cast`
+    pub fn cast<G: Float>(&self) -> Result<LayerTypes<G>, Box<dyn Error>> {
+        Ok(match self {
+            LayerTypes::Layer(layer) => LayerTypes::Layer(layer.cast()),
+            LayerTypes::Softmax(layer) => LayerTypes::Softmax(layer.cast()),
+            LayerTypes::ReLu(layer) => LayerTypes::ReLu(layer.cast()),
+            LayerTypes::LeakyReLu(layer) => LayerTypes::LeakyReLu(layer.cast()),
+            LayerTypes::ELU(layer) => LayerTypes::ELU(layer.cast()),
+            LayerTypes::SELU(layer) => LayerTypes::SELU(layer.cast()),
+            LayerTypes::Autograd(layer) => LayerTypes::Autograd(layer.cast()?),
+        })
+    }
+
+    // Copy for a data-parallel worker, trainable layers leave out the regularization gradient
+    pub fn replica(&self) -> LayerTypes<F> {
+        match self {
+            LayerTypes::Layer(layer) => LayerTypes::Layer(layer.replica()),
+            LayerTypes::Autograd(layer) => LayerTypes::Autograd(layer.replica()),
+            // activations only hold hyperparameters besides their cache
+            LayerTypes::Softmax(layer) => LayerTypes::Softmax(layer.cast()),
+            LayerTypes::ReLu(layer) => LayerTypes::ReLu(layer.cast()),
+            LayerTypes::LeakyReLu(layer) => LayerTypes::LeakyReLu(layer.cast()),
+            LayerTypes::ELU(layer) => LayerTypes::ELU(layer.cast()),
+            LayerTypes::SELU(layer) => LayerTypes::SELU(layer.cast()),
+        }
+    }
+}
+
+// weights, gradient of weight, bias, gradient of bias
+pub type ParamsGrads<'a, F = f32> = (&'a mut Array2<F>, &'a mut Array2<F>, &'a mut Array1<F>, &'a mut Array1<F>);
+
+pub trait Propagate<F: Float = f32> {
     // returns updated values i.e. 'z'
-    fn forward(&mut self, input:&Array2<f32>) -> Array2<f32>;
+    fn forward(&mut self, input:&Array2<F>) -> Array2<F>;
+
+    // same output as `forward` without caching anything for backward,
+    // so a trained model can be shared between threads for inference
+    fn infer(&self, input: &Array2<F>) -> Array2<F>;
 
     // returns gradient of that layer
-    fn backward(&mut self, grad_output: &Array2<f32>) -> Array2<f32>;
+    fn backward(&mut self, grad_output: &Array2<F>) -> Array2<F>;
 
     // Optional for trainable layers only
     // returns weights, gradient of weight, bias, gradient of bias
-    fn params_grads(&mut self) -> Option<(&mut Array2<f32>, &mut Array2<f32>, &mut Array1<f32>, &mut Array1<f32>)> {
+    fn params_grads(&mut self) -> Option<ParamsGrads<'_, F>> {
         None
     }
+
+    // Optional for trainable layers only
+    // regularization penalty added to the loss, its gradient is added in `backward`
+    fn penalty(&self) -> F {
+        F::zero()
+    }
+
+    // Optional for trainable layers only
+    // projects weights onto the layer's constraint after an optimizer step
+    fn apply_constraint(&mut self) {}
+
+    // Optional for trainable layers only
+    // frozen layers keep their weights fixed and only propagate gradient to inputs
+    fn set_frozen(&mut self, _frozen: bool) {}
+
+    fn is_frozen(&self) -> bool {
+        false
+    }
+
+    // Optional for trainable layers only
+    // accumulating layers sum gradients over backward calls instead of overwriting them
+    fn set_accumulate(&mut self, _accumulate: bool) {}
+
+    fn is_accumulating(&self) -> bool {
+        false
+    }
+
+    fn zero_grad(&mut self) {}
 }
 
-impl Propagate for LayerTypes {
-    fn forward(&mut self, input: &Array2<f32>) -> Array2<f32> {
+impl<F: Float> Propagate<F> for LayerTypes<F> {
+    fn forward(&mut self, input: &Array2<F>) -> Array2<F> {
         match self {
             LayerTypes::Layer(layer) => layer.forward(input),
             LayerTypes::Softmax(layer) => layer.forward(input),
@@ -38,33 +124,97 @@ impl Propagate for LayerTypes {
             LayerTypes::LeakyReLu(layer) => layer.forward(input),
             LayerTypes::ELU(layer) => layer.forward(input),
             LayerTypes::SELU(layer) => layer.forward(input),
+            LayerTypes::Autograd(layer) => layer.forward(input),
         }
     }
 
-    fn backward(&mut self, grad_output: &Array2<f32>) -> Array2<f32> {
+    fn infer(&self, input: &Array2<F>) -> Array2<F> {
+        match self {
+            LayerTypes::Layer(layer) => layer.infer(input),
+            LayerTypes::Softmax(layer) => layer.infer(input),
+            LayerTypes::ReLu(layer) => layer.infer(input),
+            LayerTypes::LeakyReLu(layer) => layer.infer(input),
+            LayerTypes::ELU(layer) => layer.infer(input),
+            LayerTypes::SELU(layer) => layer.infer(input),
+            LayerTypes::Autograd(layer) => layer.infer(input),
+        }
+    }
+
+    fn backward(&mut self, grad_output: &Array2<F>) -> Array2<F> {
         match self {
             LayerTypes::Layer(layer) => layer.backward(grad_output),
             LayerTypes::ReLu(layer) => layer.backward(grad_output),
             LayerTypes::LeakyReLu(layer) => layer.backward(grad_output),
             LayerTypes::ELU(layer) => layer.backward(grad_output),
             LayerTypes::SELU(layer) => layer.backward(grad_output),
+            LayerTypes::Autograd(layer) => layer.backward(grad_output),
             LayerTypes::Softmax(_) => {
                 panic!("Softmax should not be used in backprop unless combined with loss")
             }
         }
     }
 
-    fn params_grads(&mut self) -> Option<(
-        &mut Array2<f32>,
-        &mut Array2<f32>,
-        &mut Array1<f32>,
-        &mut Array1<f32>,
-    )> {
+    fn params_grads(&mut self) -> Option<ParamsGrads<'_, F>> {
         match self {
             LayerTypes::Layer(layer) => layer.params_grads(),
+            LayerTypes::Autograd(layer) => layer.params_grads(),
             _ => None,
         }
     }
+
+    fn penalty(&self) -> F {
+        match self {
+            LayerTypes::Layer(layer) => layer.penalty(),
+            LayerTypes::Autograd(layer) => layer.penalty(),
+            _ => F::zero(),
+        }
+    }
+
+    fn apply_constraint(&mut self) {
+        if let LayerTypes::Layer(layer) = self {
+            layer.apply_constraint();
+        }
+    }
+
+    fn set_frozen(&mut self, frozen: bool) {
+        match self {
+            LayerTypes::Layer(layer) => layer.set_frozen(frozen),
+            LayerTypes::Autograd(layer) => layer.set_frozen(frozen),
+            _ => {}
+        }
+    }
+
+    fn is_frozen(&self) -> bool {
+        match self {
+            LayerTypes::Layer(layer) => layer.is_frozen(),
+            LayerTypes::Autograd(layer) => layer.is_frozen(),
+            _ => false,
+        }
+    }
+
+    fn set_accumulate(&mut self, accumulate: bool) {
+        match self {
+            LayerTypes::Layer(layer) => layer.set_accumulate(accumulate),
+            LayerTypes::Autograd(layer) => layer.set_accumulate(accumulate),
+            _ => {}
+        }
+    }
+
+    fn is_accumulating(&self) -> bool {
+        match self {
+            LayerTypes::Layer(layer) => layer.is_accumulating(),
+            LayerTypes::Autograd(layer) => layer.is_accumulating(),
+            _ => false,
+        }
+    }
+
+    fn zero_grad(&mut self) {
+        match self {
+            LayerTypes::Layer(layer) => layer.zero_grad(),
+            LayerTypes::Autograd(layer) => layer.zero_grad(),
+            _ => {}
+        }
+    }
 }
 
 
@@ -73,4 +223,8 @@ pub mod relu;
 pub mod softmax;
 pub mod leaky_relu;
 pub mod elu;
-pub mod selu;
\ No newline at end of file
+pub mod selu;
+pub mod seed;
+pub mod initialization;
+pub mod regularization;
+pub mod autograd_layer;
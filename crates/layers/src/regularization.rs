@@ -0,0 +1,79 @@
+use float::num_traits::Float as _;
+use ndarray::{Array2, Axis};
+use super::Float;
+
+// Weight penalty of a layer, `penalty` is added to the loss and `gradient` is its exact derivative
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Regularization {
+    None,
+    // lambda * sum |w|
+    L1{lambda: f32},
+    // lambda * sum w²
+    L2{lambda: f32},
+    ElasticNet { l1: f32, l2: f32 },
+}
+
+impl Regularization {
+    pub fn penalty<F: Float>(&self, weights: &Array2<F>) -> F {
+        let l1 = |lambda: f32| F::from_f32(lambda) * F::sum_all(&weights.mapv(|w| w.abs()));
+        let l2 = |lambda: f32| F::from_f32(lambda) * F::sum_all(&weights.mapv(|w| w * w));
+        match *self {
+            Regularization::L1{lambda} => l1(lambda),
+            Regularization::L2{lambda} => l2(lambda),
+            Regularization::ElasticNet { l1: lambda1, l2: lambda2 } => l1(lambda1) + l2(lambda2),
+            Regularization::None => F::zero(),
+        }
+    }
+
+    // Subgradient 0 at w = 0 for the L1 term
+    pub fn gradient<F: Float>(&self, weights: &Array2<F>) -> Option<Array2<F>> {
+        let two = F::from_f32(2.0);
+        match *self {
+            Regularization::L1{lambda} => {
+                let lambda = F::from_f32(lambda);
+                Some(weights.mapv(|w| lambda * sign(w)))
+            }
+            Regularization::L2{lambda} => {
+                let lambda = F::from_f32(lambda);
+                Some(weights.mapv(|w| two * lambda * w))
+            }
+            Regularization::ElasticNet { l1, l2 } => {
+                let (l1, l2) = (F::from_f32(l1), F::from_f32(l2));
+                Some(weights.mapv(|w| l1 * sign(w) + two * l2 * w))
+            }
+            Regularization::None => None,
+        }
+    }
+}
+
+// Projection of the weights after every optimizer step, norms are over the incoming weights of each output unit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Constraint {
+    None,
+    // rescale columns whose L2 norm exceeds `max`
+    MaxNorm { max: f32 },
+    // rescale every column to L2 norm 1
+    UnitNorm,
+}
+
+impl Constraint {
+    pub fn apply<F: Float>(&self, weights: &mut Array2<F>) {
+        let target = |norm: F| match *self {
+            Constraint::MaxNorm { max } if norm > F::from_f32(max) => Some(F::from_f32(max)),
+            Constraint::UnitNorm => Some(F::one()),
+            _ => None,
+        };
+        for mut column in weights.axis_iter_mut(Axis(1)) {
+            let squares = column.iter().fold(F::Accum::from_f32(0.0), |acc, &w| acc + w.to_accum() * w.to_accum());
+            let norm = F::from_accum(squares.sqrt());
+            if let Some(target) = target(norm) {
+                let norm = norm + F::from_f32(1e-7);
+                column.mapv_inplace(|w| w * target / norm);
+            }
+        }
+    }
+}
+
+fn sign<F: Float>(w: F) -> F {
+    if w == F::zero() { F::zero() } else { w.signum() }
+}
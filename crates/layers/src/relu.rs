@@ -1,23 +1,36 @@
 use ndarray::Array2;
-use super::Propagate;
+use super::{Float, Propagate};
 
-pub struct ReLu {
-    cache: Option<Array2<f32>>,
+pub struct ReLu<F = f32> {
+    cache: Option<Array2<F>>,
 }
-impl ReLu {
+impl<F: Float> ReLu<F> {
     pub fn new() -> Self { 
         ReLu { cache: None } 
     }
+
+    // Same activation in another precision, without cache
+    pub fn cast<G: Float>(&self) -> ReLu<G> {
+        ReLu::new()
+    }
+}
+impl<F: Float> Default for ReLu<F> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
-impl Propagate for ReLu {
-    fn forward(&mut self, input: &Array2<f32>) -> Array2<f32> {
-        let mask = input.mapv(|x| x.max(0.0));
+impl<F: Float> Propagate<F> for ReLu<F> {
+    fn forward(&mut self, input: &Array2<F>) -> Array2<F> {
+        let mask = self.infer(input);
         self.cache = Some(mask.clone());
         mask
     }
-    fn backward(&mut self, grad_output: &Array2<f32>) -> Array2<f32> {
+    fn infer(&self, input: &Array2<F>) -> Array2<F> {
+        input.mapv(|x| x.max(F::zero()))
+    }
+    fn backward(&mut self, grad_output: &Array2<F>) -> Array2<F> {
         let mask = self.cache.as_ref().expect("No cache");
-        let temp = mask.mapv(|x| if x > 0.0 { 1.0 } else { 0.0 });
+        let temp = mask.mapv(|x| if x > F::zero() { F::one() } else { F::zero() });
         grad_output * temp
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,34 @@
+use std::cell::RefCell;
+
+use ndarray_rand::rand::{RngCore, SeedableRng, rngs::StdRng};
+
+thread_local! {
+    // Generator behind weight initialization, one per thread so that threads seeding their own
+    // models (e.g. parallel tests) never draw from each other's stream. None draws from OS entropy.
+    static THREAD_RNG: RefCell<Option<StdRng>> = const { RefCell::new(None) };
+}
+
+// Makes every following initialization on this thread reproducible. Together with the explicit seeds of
+// `DataLoader::shuffle` and `Augmenter` (e.g. from `next_seed`) a seed replays a whole training run.
+pub fn set_seed(seed: u64) {
+    THREAD_RNG.with(|rng| *rng.borrow_mut() = Some(StdRng::seed_from_u64(seed)));
+}
+
+// Back to OS entropy
+pub fn clear_seed() {
+    THREAD_RNG.with(|rng| *rng.borrow_mut() = None);
+}
+
+// Runs `f` with a generator seeded from the thread's one rather than the thread's generator itself,
+// so `f` may build layers or call `next_seed` (e.g. in an `Initialization::Custom` closure)
+pub fn with_rng<T>(f: impl FnOnce(&mut StdRng) -> T) -> T {
+    f(&mut StdRng::seed_from_u64(next_seed()))
+}
+
+// Seed for components with their own generator, drawn from the thread's one
+pub fn next_seed() -> u64 {
+    THREAD_RNG.with(|rng| match rng.borrow_mut().as_mut() {
+        Some(rng) => rng.next_u64(),
+        None => StdRng::from_entropy().next_u64(),
+    })
+}
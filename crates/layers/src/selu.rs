@@ -1,12 +1,12 @@
 use ndarray::Array2;
-use super::Propagate;
+use super::{Float, Propagate};
 
-pub struct SELU {
+pub struct SELU<F = f32> {
     alpha: f32,
     scale: f32,
-    cache: Option<Array2<f32>>,
+    cache: Option<Array2<F>>,
 }
-impl SELU {
+impl<F: Float> SELU<F> {
     pub fn new(alpha: f32, scale: f32) -> Self {
         SELU { 
             alpha,
@@ -14,18 +14,28 @@ impl SELU {
             cache: None 
         }
     }
+
+    // Same activation in another precision, without cache
+    pub fn cast<G: Float>(&self) -> SELU<G> {
+        SELU::new(self.alpha, self.scale)
+    }
 }
-impl Propagate for SELU {
-    fn forward(&mut self, input: &Array2<f32>) -> Array2<f32> {
+impl<F: Float> Propagate<F> for SELU<F> {
+    fn forward(&mut self, input: &Array2<F>) -> Array2<F> {
         self.cache = Some(input.clone());
+        self.infer(input)
+    }
 
-        input.mapv(|x| { self.scale * (if x > 0.0 { x } else { self.alpha * (x.exp() - 1.0) }) })
+    fn infer(&self, input: &Array2<F>) -> Array2<F> {
+        let (alpha, scale) = (F::from_f32(self.alpha), F::from_f32(self.scale));
+        input.mapv(|x| { scale * (if x > F::zero() { x } else { alpha * (x.exp() - F::one()) }) })
     }
 
-    fn backward(&mut self, grad_output: &Array2<f32>) -> Array2<f32> {
+    fn backward(&mut self, grad_output: &Array2<F>) -> Array2<F> {
         let input = self.cache.as_ref().expect("No cache");
+        let (alpha, scale) = (F::from_f32(self.alpha), F::from_f32(self.scale));
 
-        let grad = input.mapv(|x| { self.scale * if x > 0.0 { 1.0 } else { self.alpha * x.exp() } });
+        let grad = input.mapv(|x| { scale * if x > F::zero() { F::one() } else { alpha * x.exp() } });
         grad_output * grad
     }
 }
@@ -1,37 +1,52 @@
 use ndarray::{Array2, Axis};
-use super::Propagate;
+use super::{Float, Propagate};
 
-pub struct Softmax {
-    pub output: Option<Array2<f32>>,
+pub struct Softmax<F = f32> {
+    pub output: Option<Array2<F>>,
 }
 
-impl Softmax {
+impl<F: Float> Softmax<F> {
     pub fn new() -> Self {
         Softmax { output: None }
     }
+
+    // Same activation in another precision, without cache
+    pub fn cast<G: Float>(&self) -> Softmax<G> {
+        Softmax::new()
+    }
+}
+
+impl<F: Float> Default for Softmax<F> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl Propagate for Softmax {
-    fn forward(&mut self, input: &Array2<f32>) -> Array2<f32> {
+impl<F: Float> Propagate<F> for Softmax<F> {
+    fn forward(&mut self, input: &Array2<F>) -> Array2<F> {
+        let output = self.infer(input);
+        self.output = Some(output.clone());
+        output
+    }
+
+    fn infer(&self, input: &Array2<F>) -> Array2<F> {
         let mut output = input.clone();
 
         // Axis(0) in 2D Array means rows
         // Axis(1) in 2D Array means cols 
         for mut row in output.axis_iter_mut(Axis(0)) {
             // For each row i.e. all classes of a label subtract max for numerical stability
-            let max = row.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+            let max = row.iter().cloned().fold(F::neg_infinity(), F::max);
             row.mapv_inplace(|x| (x - max).exp());
 
-            // Divide by the sum of exponentials
-            let sum = row.sum();
-            row.mapv_inplace(|x| x / sum);
+            // Divide by the sum of exponentials, summed in the accumulation type
+            let sum = row.iter().fold(F::Accum::from_f32(0.0), |acc, &x| acc + x.to_accum());
+            row.mapv_inplace(|x| F::from_accum(x.to_accum() / sum));
         }
-
-        self.output = Some(output.clone());
         output
     }
 
-    fn backward(&mut self, _grad_output: &Array2<f32>) -> Array2<f32> {
+    fn backward(&mut self, _grad_output: &Array2<F>) -> Array2<F> {
         panic!("Softmax gradient should be handled in cross-entropy loss");
     }
-}
\ No newline at end of file
+}
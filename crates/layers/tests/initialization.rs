@@ -0,0 +1,141 @@
+use std::sync::{Arc, Mutex};
+
+use layers::*;
+use ndarray::{Array1, Array2, array};
+use ndarray_rand::rand::{SeedableRng, rngs::StdRng};
+
+const FAN_IN: usize = 200;
+const FAN_OUT: usize = 300;
+
+fn weights(layer: &mut Layer) -> Array2<f32> {
+    layer.params_grads().unwrap().0.clone()
+}
+
+fn bias(layer: &mut Layer) -> Array1<f32> {
+    layer.params_grads().unwrap().2.clone()
+}
+
+fn sample(init: Initialization) -> Array2<f32> {
+    init.sample((FAN_IN, FAN_OUT), FAN_IN, FAN_OUT, &mut StdRng::seed_from_u64(1))
+}
+
+fn std(values: &Array2<f32>) -> f32 {
+    values.std(0.0)
+}
+
+// within 3% of the expected std, the sample has 60000 values
+fn assert_std(init: Initialization, expected: f32) {
+    let values = sample(init);
+    assert!(values.mean().unwrap().abs() < 0.05 * expected);
+    assert!((std(&values) / expected - 1.0).abs() < 0.03, "std {} vs {}", std(&values), expected);
+}
+
+fn assert_uniform(init: Initialization, limit: f32) {
+    let values = sample(init);
+    assert!(values.iter().all(|v| v.abs() <= limit));
+    // uniform in ±limit has std limit / sqrt(3) and reaches close to the limit
+    assert!((std(&values) / (limit / 3f32.sqrt()) - 1.0).abs() < 0.03);
+    assert!(values.iter().any(|v| v.abs() > 0.99 * limit));
+}
+
+#[test]
+fn normal_initializations_scale_with_fans() {
+    assert_std(Initialization::He, (2.0 / FAN_IN as f32).sqrt());
+    assert_std(Initialization::Glorot, (2.0 / (FAN_IN + FAN_OUT) as f32).sqrt());
+    assert_std(Initialization::LeCun, (1.0 / FAN_IN as f32).sqrt());
+}
+
+#[test]
+fn uniform_initializations_stay_within_limits() {
+    assert_uniform(Initialization::HeUniform, (6.0 / FAN_IN as f32).sqrt());
+    assert_uniform(Initialization::GlorotUniform, (6.0 / (FAN_IN + FAN_OUT) as f32).sqrt());
+    assert_uniform(Initialization::LeCunUniform, (3.0 / FAN_IN as f32).sqrt());
+}
+
+#[test]
+fn truncated_normal_redraws_beyond_two_std() {
+    let values = sample(Initialization::TruncatedNormal { std: 0.5 });
+    assert!(values.iter().all(|v| v.abs() <= 1.0));
+    assert!(values.iter().any(|v| v.abs() > 0.9));
+    // a normal truncated at 2 std keeps about 88% of its std
+    assert!((std(&values) - 0.5 * 0.88).abs() < 0.01, "{}", std(&values));
+}
+
+#[test]
+fn orthogonal_columns_or_rows_are_orthonormal() {
+    let identity = |n: usize| Array2::<f32>::eye(n);
+    let close = |a: &Array2<f32>, b: &Array2<f32>| a.iter().zip(b.iter()).all(|(x, y)| (x - y).abs() < 1e-4);
+    let mut rng = StdRng::seed_from_u64(2);
+
+    // tall: WᵀW = I
+    let w = Initialization::Orthogonal { gain: 1.0 }.sample((8, 5), 8, 5, &mut rng);
+    assert!(close(&w.t().dot(&w), &identity(5)));
+    // wide: WWᵀ = I
+    let w = Initialization::Orthogonal { gain: 1.0 }.sample((5, 8), 5, 8, &mut rng);
+    assert!(close(&w.dot(&w.t()), &identity(5)));
+    // gain scales the norms
+    let w = Initialization::Orthogonal { gain: 2.0 }.sample((6, 6), 6, 6, &mut rng);
+    assert!(close(&w.t().dot(&w), &(identity(6) * 4.0)));
+}
+
+#[test]
+fn constant_and_zeros() {
+    assert!(sample(Initialization::Constant(0.25)).iter().all(|&v| v == 0.25));
+    assert!(sample(Initialization::Zeros).iter().all(|&v| v == 0.0));
+}
+
+// fills the array with one value drawn from the generator
+fn draw_once() -> Initialization {
+    Initialization::Custom(Arc::new(|shape, rng| Array2::from_elem(shape, (rng.next_u32() % 7) as f32)))
+}
+
+#[test]
+fn custom_closure_gets_the_shape_and_generator() {
+    let shapes = Arc::new(Mutex::new(Vec::new()));
+    let seen = shapes.clone();
+    let init = Initialization::Custom(Arc::new(move |shape, rng| {
+        seen.lock().unwrap().push(shape);
+        Array2::from_elem(shape, (rng.next_u32() % 7) as f32)
+    }));
+
+    let mut layer = Layer::new_with_rng(3, 2, init.clone(), Regularization::None, &mut StdRng::seed_from_u64(5)).with_bias(init);
+    assert_eq!(*shapes.lock().unwrap(), vec![(3, 2), (1, 2)]);
+    assert_eq!(weights(&mut layer).dim(), (3, 2));
+
+    // the closure draws from the generator it is handed
+    let mut again = Layer::new_with_rng(3, 2, draw_once(), Regularization::None, &mut StdRng::seed_from_u64(5));
+    assert_eq!(weights(&mut again), weights(&mut layer));
+}
+
+#[test]
+#[should_panic(expected = "wrong shape")]
+fn custom_closure_with_wrong_shape_panics() {
+    let init = Initialization::Custom(Arc::new(|_, _| Array2::zeros((1, 1))));
+    Layer::<f32>::new(3, 2, init, Regularization::None);
+}
+
+#[test]
+fn bias_starts_at_zero_unless_initialized() {
+    set_seed(3);
+    let mut layer = Layer::new(4, 3, Initialization::He, Regularization::None);
+    assert_eq!(bias(&mut layer), array![0.0, 0.0, 0.0]);
+
+    let mut layer = layer.with_bias(Initialization::Constant(0.01));
+    assert_eq!(bias(&mut layer), array![0.01, 0.01, 0.01]);
+
+    // bias uses the layer's fans, not its own (1, units) shape
+    let mut layer = Layer::new(400, 600, Initialization::Zeros, Regularization::None).with_bias(Initialization::GlorotUniform);
+    let limit = (6.0f32 / 1000.0).sqrt();
+    assert!(bias(&mut layer).iter().all(|b| b.abs() <= limit));
+    assert!(bias(&mut layer).iter().any(|b| b.abs() > 0.5 * limit));
+}
+
+#[test]
+fn set_seed_makes_layers_reproducible() {
+    let layer = |seed| {
+        set_seed(seed);
+        weights(&mut Layer::new(5, 4, Initialization::Glorot, Regularization::None))
+    };
+    assert_eq!(layer(8), layer(8));
+    assert_ne!(layer(8), layer(9));
+}
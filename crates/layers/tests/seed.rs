@@ -0,0 +1,45 @@
+use std::sync::{Arc, Mutex};
+
+use layers::*;
+use ndarray::Array2;
+use ndarray_rand::rand::RngCore;
+
+// weights of a layer whose initializer builds another layer and draws a seed
+fn nested(seed: u64) -> (Array2<f32>, Vec<u64>) {
+    let seeds = Arc::new(Mutex::new(Vec::new()));
+    let drawn = seeds.clone();
+    let init = Initialization::Custom(Arc::new(move |shape, _| {
+        let mut inner = Layer::<f32>::new(shape.0, shape.1, Initialization::He, Regularization::None);
+        drawn.lock().unwrap().push(next_seed());
+        inner.params_grads().unwrap().0.clone()
+    }));
+
+    set_seed(seed);
+    let mut layer = Layer::<f32>::new(3, 2, init, Regularization::None);
+    let weights = layer.params_grads().unwrap().0.clone();
+    let seeds = seeds.lock().unwrap().clone();
+    (weights, seeds)
+}
+
+#[test]
+fn initializers_can_draw_from_the_thread_generator() {
+    assert_eq!(nested(4), nested(4));
+    assert_ne!(nested(4), nested(5));
+}
+
+#[test]
+fn with_rng_nests() {
+    set_seed(1);
+    let (outer, inner) = with_rng(|rng| (rng.next_u64(), with_rng(|rng| rng.next_u64())));
+    assert_ne!(outer, inner);
+}
+
+#[test]
+fn clear_seed_draws_from_entropy() {
+    set_seed(2);
+    let seeded = next_seed();
+    clear_seed();
+    assert_ne!(next_seed(), next_seed());
+    set_seed(2);
+    assert_eq!(next_seed(), seeded);
+}
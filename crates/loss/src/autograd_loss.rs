@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use ndarray::Array2;
+use layers::{Float, Tape, Var};
+use super::Loss;
+
+// (preds, targets as a constant) -> 1x1 loss, recorded on one tape of `T`
+pub type LossFn<T = f32> = Arc<dyn for<'t> Fn(Var<'t, T>, Var<'t, T>) -> Var<'t, T> + Send + Sync>;
+
+// Loss whose gradient is derived from its forward ops, e.g. mean squared error on the raw outputs:
+// AutogradLoss::new(Arc::new(|preds, targets| (preds - targets).powi(2).mean()))
+// The tape computes in `T`, the `Float::Accum` of the model: f32 for f32, f16 and bf16 models, f64 for f64 models.
+pub struct AutogradLoss<T = f32> {
+    pub loss: LossFn<T>,
+    // gradient with respect to the predictions of the last `forward`
+    pub grad: Option<Array2<T>>,
+}
+
+impl<T: Float> AutogradLoss<T> {
+    pub fn new(loss: LossFn<T>) -> Self {
+        AutogradLoss { loss, grad: None }
+    }
+}
+
+impl<F: Float> Loss<F> for AutogradLoss<F::Accum> {
+    fn forward(&mut self, preds: &Array2<F>, targets: &Array2<F>) -> F {
+        let tape = Tape::new();
+        let preds_var = tape.var(preds.mapv(F::to_accum));
+        let loss = (self.loss)(preds_var, tape.var(targets.mapv(F::to_accum)));
+        let grads = loss.backward();
+        self.grad = Some(grads.wrt(preds_var).cloned().unwrap_or_else(|| Array2::zeros(preds.dim())));
+        F::from_accum(loss.value()[[0, 0]])
+    }
+
+    fn backward(&self, _preds: &Array2<F>) -> Array2<F> {
+        self.grad.as_ref().expect("No cached gradient").mapv(F::from_accum)
+    }
+}
@@ -1,13 +1,13 @@
 use ndarray::{Array1, Array2, Axis};
-use layers::{Propagate, Softmax};
+use layers::{Float, Propagate, Softmax};
 use super::Loss;
 
-pub struct CrossEntropyLoss {
-    pub probs: Option<Array2<f32>>,
-    pub one_hot_encoded: Option<Array2<f32>>,
+pub struct CrossEntropyLoss<F = f32> {
+    pub probs: Option<Array2<F>>,
+    pub one_hot_encoded: Option<Array2<F>>,
 }
 
-impl CrossEntropyLoss {
+impl<F: Float> CrossEntropyLoss<F> {
     pub fn new() -> Self {
         CrossEntropyLoss {
             probs: None,
@@ -16,26 +16,36 @@ impl CrossEntropyLoss {
     }
 }
 
-impl Loss for CrossEntropyLoss {
-    fn forward(&mut self, preds: &Array2<f32>, targets: &Array2<f32>) -> f32 {
+impl<F: Float> Default for CrossEntropyLoss<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Float> Loss<F> for CrossEntropyLoss<F> {
+    fn forward(&mut self, preds: &Array2<F>, targets: &Array2<F>) -> F {
         let probs = Softmax::new().forward(preds);
-        let mut one_hot = Array2::<f32>::zeros((targets.len_of(Axis(0)), probs.len_of(Axis(1))));
-        let labels: Array1<usize> = targets.iter().map(|x| *x as usize).collect();
+        let mut one_hot = Array2::<F>::zeros((targets.len_of(Axis(0)), probs.len_of(Axis(1))));
+        let labels: Array1<usize> = targets.iter().map(|x| x.into_f32() as usize).collect();
 
         for (i, &class_idx) in labels.iter().enumerate() {
-            one_hot[[i, class_idx]] = 1.0;
+            one_hot[[i, class_idx]] = F::one();
         }
 
         self.probs = Some(probs.clone());
         self.one_hot_encoded = Some(one_hot.clone());
 
-        let log_probs = probs.mapv(|p| p.max(1e-9).ln());
-        -(one_hot * log_probs).mean().unwrap()
+        // mean over samples, matching the 1 / batch size of `backward`
+        // 1e-9 is below the smallest normal half precision value, which then takes its place
+        let floor = F::from_f32(1e-9).max(F::min_positive_value());
+        let log_probs = probs.mapv(|p| p.max(floor).ln());
+        -F::sum_all(&(one_hot * log_probs)) / F::from_f32(probs.len_of(Axis(0)) as f32)
     }
 
-    fn backward(&self, _logits: &Array2<f32>) -> Array2<f32> {
+    fn backward(&self, _logits: &Array2<F>) -> Array2<F> {
         let probs = self.probs.as_ref().unwrap();
         let one_hot = self.one_hot_encoded.as_ref().unwrap();
-        (probs - one_hot) / (probs.len_of(Axis(0)) as f32)
+        let n = F::from_f32(probs.len_of(Axis(0)) as f32);
+        (probs - one_hot).mapv(|g| g / n)
     }
 }
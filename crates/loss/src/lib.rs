@@ -1,14 +1,17 @@
 use ndarray::Array2;
+use layers::Float;
 pub use mseloss::MSELoss;
 pub use cross_entropyloss::CrossEntropyLoss;
+pub use autograd_loss::{AutogradLoss, LossFn};
 
-pub trait Loss {
+pub trait Loss<F: Float = f32> {
     // returns loss
-    fn forward(&mut self, preds: &Array2<f32>, targets: &Array2<f32>) -> f32;
+    fn forward(&mut self, preds: &Array2<F>, targets: &Array2<F>) -> F;
     
     // returns gradient
-    fn backward(&self, preds: &Array2<f32>) -> Array2<f32>;
+    fn backward(&self, preds: &Array2<F>) -> Array2<F>;
 }
 
 pub mod mseloss;
-pub mod cross_entropyloss;
\ No newline at end of file
+pub mod cross_entropyloss;
+pub mod autograd_loss;
\ No newline at end of file
@@ -1,36 +1,45 @@
 use ndarray::{Array1, Array2, Axis};
-use layers::{Propagate, Softmax};
+use layers::{Float, Propagate, Softmax};
 use super::Loss;
 
-pub struct MSELoss{
-    pub probs: Option<Array2<f32>>,
-    pub one_hot_encoded: Option<Array2<f32>>,
+pub struct MSELoss<F = f32>{
+    pub probs: Option<Array2<F>>,
+    pub one_hot_encoded: Option<Array2<F>>,
 }
-impl MSELoss {
+impl<F: Float> MSELoss<F> {
     pub fn new() -> Self {
         MSELoss { probs: None, one_hot_encoded: None }
     }
 }
-impl Loss for MSELoss{ 
-    fn forward(&mut self, preds: &Array2<f32>, targets: &Array2<f32>) -> f32 {
+impl<F: Float> Default for MSELoss<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<F: Float> Loss<F> for MSELoss<F>{ 
+    fn forward(&mut self, preds: &Array2<F>, targets: &Array2<F>) -> F {
         let mut softmax_layer = Softmax::new();
         let probs = softmax_layer.forward(preds);
 
-        let mut one_hot = Array2::<f32>::zeros((targets.len_of(Axis(0)), probs.len_of(Axis(1))));
-        let labels: Array1<usize> = targets.iter().map(|x| *x as usize).collect();
+        let mut one_hot = Array2::<F>::zeros((targets.len_of(Axis(0)), probs.len_of(Axis(1))));
+        let labels: Array1<usize> = targets.iter().map(|x| x.into_f32() as usize).collect();
 
         for (i, &class_idx) in labels.iter().enumerate() {
-            one_hot[[i, class_idx]] = 1.0;
+            one_hot[[i, class_idx]] = F::one();
         }
 
         self.one_hot_encoded = Some(one_hot.clone());
         self.probs = Some(probs.clone());
         let diff = probs - one_hot;
-        diff.mapv(|x| x.powi(2)).mean().unwrap()
+        F::sum_all(&diff.mapv(|x| x.powi(2))) / F::from_f32(diff.len() as f32)
     }
-    fn backward(&self, preds: &Array2<f32>) -> Array2<f32> {
+    fn backward(&self, preds: &Array2<F>) -> Array2<F> {
         let probabilities = self.probs.as_ref().expect("No cached probabilities");
         let one_hot = self.one_hot_encoded.as_ref().expect("No cached targets");
-        (2.0 / (preds.len() as f32)) * (probabilities - one_hot)
+        // gradient with respect to the probabilities, then through the softmax Jacobian to the logits
+        let scale = F::from_f32(2.0 / (preds.len() as f32));
+        let grad_probs = (probabilities - one_hot).mapv(|g| scale * g);
+        let dot = F::sum_axis(&(&grad_probs * probabilities), Axis(1)).insert_axis(Axis(1));
+        probabilities * &(grad_probs - dot)
     }
 }
\ No newline at end of file
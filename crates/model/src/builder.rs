@@ -0,0 +1,96 @@
+use std::error::Error;
+
+use super::{NN, Float, Initialization, Layer, LayerTypes, Loss, Regularization};
+
+// Builds an `NN` layer by layer from the input width, e.g.
+// NNBuilder::new(784).dense(64, Initialization::He, Regularization::None).layer(LayerTypes::ELU(ELU::new(1.0))).dense(10, ...)
+// The element type `F` follows from the layers, loss and optimizer, e.g. `NNBuilder::<f64>::new(784)`
+pub struct NNBuilder<F: Float = f32> {
+    input_dim: usize,
+    layers: Vec<LayerTypes<F>>,
+}
+
+impl<F: Float> NNBuilder<F> {
+    pub fn new(input_dim: usize) -> Self {
+        NNBuilder {
+            input_dim,
+            layers: Vec::new(),
+        }
+    }
+
+    // Fully connected layer whose input width is the output width of the previous layer
+    pub fn dense(mut self, units: usize, initialization: Initialization, regularization: Regularization) -> Self {
+        let input_dim = self.output_dim();
+        self.layers.push(LayerTypes::Layer(Layer::new(input_dim, units, initialization, regularization)));
+        self
+    }
+
+    // Any prebuilt layer, its input width is checked by `build`
+    pub fn layer(mut self, layer: LayerTypes<F>) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    // Checks that every layer accepts the output of the one before it
+    pub fn build<S: Loss<F>, O>(self, loss_fn: S, optim: O) -> Result<NN<S, O, F>, Box<dyn Error>> {
+        if self.input_dim == 0 {
+            return Err("input width must be positive".into());
+        }
+        if self.layers.is_empty() {
+            return Err("model has no layers".into());
+        }
+
+        let mut width = self.input_dim;
+        for (idx, layer) in self.layers.iter().enumerate() {
+            match layer {
+                LayerTypes::Layer(layer) => {
+                    if layer.input_dim() != width {
+                        return Err(format!(
+                            "layer {} (Layer {}x{}) expects input width {} but receives width {}",
+                            idx, layer.input_dim(), layer.output_dim(), layer.input_dim(), width
+                        ).into());
+                    }
+                    if layer.output_dim() == 0 {
+                        return Err(format!("layer {} (Layer) has no outputs", idx).into());
+                    }
+                    width = layer.output_dim();
+                }
+                LayerTypes::Autograd(layer) => {
+                    if layer.input_dim() != width {
+                        return Err(format!(
+                            "layer {} (Autograd) expects input width {} but receives width {}",
+                            idx, layer.input_dim(), width
+                        ).into());
+                    }
+                    width = layer.output_dim();
+                    if width == 0 {
+                        return Err(format!("layer {} (Autograd) has no outputs", idx).into());
+                    }
+                }
+                LayerTypes::Softmax(_) => {
+                    return Err(format!("layer {} is Softmax, softmax is applied by the loss and the metrics so leave it out", idx).into());
+                }
+                // activations keep the width
+                _ => {}
+            }
+        }
+
+        Ok(NN {
+            layers: self.layers,
+            loss_fn,
+            optim,
+        })
+    }
+
+    fn output_dim(&self) -> usize {
+        self.layers
+            .iter()
+            .rev()
+            .find_map(|l| match l {
+                LayerTypes::Layer(layer) => Some(layer.output_dim()),
+                LayerTypes::Autograd(layer) => Some(layer.output_dim()),
+                _ => None,
+            })
+            .unwrap_or(self.input_dim)
+    }
+}
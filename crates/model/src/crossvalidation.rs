@@ -0,0 +1,91 @@
+use std::fmt;
+
+use ndarray::{Array2, Axis};
+
+use super::{NN, Float, Loss, Propagate, Softmax};
+
+// returns score from (probabilities, targets), e.g. `accuracy`
+pub type Metric = fn(&Array2<f32>, &Array2<f32>) -> f32;
+
+pub struct CrossValidation {
+    pub metric_names: Vec<String>,
+    // shape (number of folds, number of metrics)
+    pub fold_scores: Array2<f32>,
+}
+
+impl CrossValidation {
+    // mean of every metric over folds, None without folds
+    pub fn mean(&self) -> Option<Vec<f32>> {
+        self.fold_scores.mean_axis(Axis(0)).map(|mean| mean.to_vec())
+    }
+
+    // population standard deviation over folds, None without folds
+    pub fn std(&self) -> Option<Vec<f32>> {
+        (self.fold_scores.nrows() > 0).then(|| self.fold_scores.std_axis(Axis(0), 0.0).to_vec())
+    }
+}
+
+impl fmt::Display for CrossValidation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (fold, scores) in self.fold_scores.outer_iter().enumerate() {
+            let line: Vec<String> = self.metric_names
+                .iter()
+                .zip(scores.iter())
+                .map(|(name, score)| format!("{} = {}", name, score))
+                .collect();
+            writeln!(f, "Fold {}: {}", fold, line.join(", "))?;
+        }
+        match (self.mean(), self.std()) {
+            (Some(mean), Some(std)) => {
+                let lines: Vec<String> = self.metric_names
+                    .iter()
+                    .zip(mean.iter().zip(std.iter()))
+                    .map(|(name, (mean, std))| format!("{}: {} ± {}", name, mean, std))
+                    .collect();
+                write!(f, "{}", lines.join("\n"))
+            }
+            _ => write!(f, "No folds"),
+        }
+    }
+}
+
+// Trains a fresh model from `factory` on every fold and scores it on the fold's validation rows.
+// Metrics see probabilities and targets as f32 whatever the model's element type.
+// `folds` are (train indices, validation indices), `train` runs the whole training of one model.
+pub fn cross_validate<F, S, O, B, T>(
+    x: &Array2<F>,
+    y: &Array2<F>,
+    folds: &[(Vec<usize>, Vec<usize>)],
+    mut factory: B,
+    mut train: T,
+    metrics: &[(&str, Metric)],
+) -> CrossValidation
+where
+    F: Float,
+    S: Loss<F>,
+    B: FnMut() -> NN<S, O, F>,
+    T: FnMut(&mut NN<S, O, F>, &Array2<F>, &Array2<F>),
+{
+    let mut fold_scores = Array2::zeros((folds.len(), metrics.len()));
+
+    for (fold, (train_rows, val_rows)) in folds.iter().enumerate() {
+        let x_train = x.select(Axis(0), train_rows);
+        let y_train = y.select(Axis(0), train_rows);
+        let x_val = x.select(Axis(0), val_rows);
+        let y_val = y.select(Axis(0), val_rows);
+
+        let mut nn = factory();
+        train(&mut nn, &x_train, &y_train);
+
+        let probs = Softmax::new().forward(&nn.forward_all(&x_val)).mapv(F::into_f32);
+        let y_val = y_val.mapv(F::into_f32);
+        for (m, (_, metric)) in metrics.iter().enumerate() {
+            fold_scores[[fold, m]] = metric(&probs, &y_val);
+        }
+    }
+
+    CrossValidation {
+        metric_names: metrics.iter().map(|(name, _)| name.to_string()).collect(),
+        fold_scores,
+    }
+}
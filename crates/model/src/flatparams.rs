@@ -0,0 +1,41 @@
+use ndarray::Array1;
+use layers::{Float, LayerTypes, Propagate};
+
+// Weights then bias of every trainable layer that is not frozen, in layer order
+pub fn flatten_params<F: Float>(layers: &mut [LayerTypes<F>]) -> Array1<F> {
+    let mut flat = Vec::new();
+    for layer in layers.iter_mut().filter(|l| !l.is_frozen()) {
+        if let Some((weights, _, bias, _)) = layer.params_grads() {
+            flat.extend(weights.iter());
+            flat.extend(bias.iter());
+        }
+    }
+    Array1::from(flat)
+}
+
+// Gradients in the same order as `flatten_params`
+pub fn flatten_grads<F: Float>(layers: &mut [LayerTypes<F>]) -> Array1<F> {
+    let mut flat = Vec::new();
+    for layer in layers.iter_mut().filter(|l| !l.is_frozen()) {
+        if let Some((_, grad_weights, _, grad_bias)) = layer.params_grads() {
+            flat.extend(grad_weights.iter());
+            flat.extend(grad_bias.iter());
+        }
+    }
+    Array1::from(flat)
+}
+
+// Inverse of `flatten_params`, writes `flat` back into the layers
+pub fn unflatten_params<F: Float>(layers: &mut [LayerTypes<F>], flat: &Array1<F>) {
+    let mut values = flat.iter();
+    for layer in layers.iter_mut().filter(|l| !l.is_frozen()) {
+        if let Some((weights, _, bias, _)) = layer.params_grads() {
+            for (w, v) in weights.iter_mut().zip(values.by_ref()) {
+                *w = *v;
+            }
+            for (b, v) in bias.iter_mut().zip(values.by_ref()) {
+                *b = *v;
+            }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use ndarray::{Array1, Array2};
+
+use super::{NN, Float, LayerTypes, Loss, Propagate, flatten_grads, flatten_params, unflatten_params};
+
+// Gradients smaller than this are compared by absolute error, f32 differences are too noisy below it
+const ERROR_FLOOR: f64 = 1e-2;
+
+#[derive(Debug, Clone, Copy)]
+pub struct GradCheck {
+    // max relative error of the gradient with respect to the inputs
+    pub input_error: f32,
+    // max relative error over weights and bias, None without trainable parameters
+    pub param_error: Option<f32>,
+}
+
+impl GradCheck {
+    pub fn max_error(&self) -> f32 {
+        self.input_error.max(self.param_error.unwrap_or(0.0))
+    }
+}
+
+// Compares `backward` of `layer` with central differences of `eps` through `infer`.
+// The layer is checked on the scalar sum(output * r) + penalty for a fixed r, it must not accumulate gradients.
+// In f64 a much smaller `eps` (e.g. 1e-6) gives a much tighter check.
+pub fn gradcheck<F: Float, P: Propagate<F>>(layer: &mut P, x: &Array2<F>, eps: f32) -> GradCheck {
+    let x = x.as_standard_layout().to_owned();
+    let out = layer.forward(&x);
+    let r = projection(out.dim());
+    let grad_input = layer.backward(&r);
+
+    let objective = |layer: &P, x: &Array2<F>| {
+        (layer.infer(x) * &r).mapv(F::into_f64).sum() + layer.penalty().into_f64()
+    };
+
+    let numeric_input = perturbed(&x, eps, |x_eps| objective(layer, x_eps));
+    let input_error = max_relative_error(grad_input.iter(), numeric_input.iter());
+
+    let param_error = match layer.params_grads() {
+        None => None,
+        Some((weights, grad_weights, bias, grad_bias)) => {
+            let analytic: Vec<F> = grad_weights.iter().chain(grad_bias.iter()).copied().collect();
+            let params: Vec<F> = weights.iter().chain(bias.iter()).copied().collect();
+            let num_weights = weights.len();
+            let numeric = central_differences(params.len(), eps, |i, delta| {
+                set_param(layer, i, num_weights, params[i] + F::from_f32(delta));
+                let value = objective(layer, &x);
+                set_param(layer, i, num_weights, params[i]);
+                value
+            });
+            Some(max_relative_error(analytic.iter(), numeric.iter()))
+        }
+    };
+
+    GradCheck { input_error, param_error }
+}
+
+// Compares `Loss::backward` with central differences of `Loss::forward` with respect to the predictions
+pub fn gradcheck_loss<F: Float, L: Loss<F>>(loss_fn: &mut L, preds: &Array2<F>, targets: &Array2<F>, eps: f32) -> f32 {
+    let preds = preds.as_standard_layout().to_owned();
+    loss_fn.forward(&preds, targets);
+    let analytic = loss_fn.backward(&preds);
+
+    let numeric = perturbed(&preds, eps, |preds_eps| loss_fn.forward(preds_eps, targets).into_f64());
+    max_relative_error(analytic.iter(), numeric.iter())
+}
+
+// Compares the gradients of the whole model (loss plus every layer's penalty) with central differences,
+// with respect to the inputs and all parameters that are not frozen. Layers must not accumulate gradients.
+pub fn gradcheck_model<F: Float, S: Loss<F>, O>(nn: &mut NN<S, O, F>, x: &Array2<F>, y: &Array2<F>, eps: f32) -> GradCheck {
+    let x = x.as_standard_layout().to_owned();
+    let preds = nn.forward_all(&x);
+    nn.loss_fn.forward(&preds, y);
+    // every layer, so the gradient reaches the inputs
+    let mut grad = nn.loss_fn.backward(&preds);
+    for layer in nn.layers.iter_mut().rev() {
+        grad = layer.backward(&grad);
+    }
+    let grad_input = grad;
+    let analytic_params = flatten_grads(&mut nn.layers);
+
+    let objective = |layers: &[LayerTypes<F>], loss_fn: &mut S, x: &Array2<F>| {
+        let preds = layers.iter().fold(x.clone(), |z, layer| layer.infer(&z));
+        loss_fn.forward(&preds, y).into_f64() + layers.iter().map(|l| l.penalty().into_f64()).sum::<f64>()
+    };
+
+    let numeric_input = perturbed(&x, eps, |x_eps| objective(&nn.layers, &mut nn.loss_fn, x_eps));
+    let input_error = max_relative_error(grad_input.iter(), numeric_input.iter());
+
+    let params = flatten_params(&mut nn.layers);
+    let param_error = if params.is_empty() {
+        None
+    } else {
+        let mut params_eps = params.clone();
+        let numeric = central_differences(params.len(), eps, |i, delta| {
+            params_eps[i] = params[i] + F::from_f32(delta);
+            unflatten_params(&mut nn.layers, &params_eps);
+            let value = objective(&nn.layers, &mut nn.loss_fn, &x);
+            params_eps[i] = params[i];
+            value
+        });
+        unflatten_params(&mut nn.layers, &params);
+        Some(max_relative_error(analytic_params.iter(), numeric.iter()))
+    };
+
+    GradCheck { input_error, param_error }
+}
+
+// Central differences of `f` with respect to every value of `values`, in row-major order
+fn perturbed<F: Float, O: FnMut(&Array2<F>) -> f64>(values: &Array2<F>, eps: f32, mut f: O) -> Array1<f64> {
+    let mut values_eps = values.clone();
+    central_differences(values.len(), eps, |i, delta| {
+        values_eps.as_slice_mut().unwrap()[i] = values.as_slice().unwrap()[i] + F::from_f32(delta);
+        let value = f(&values_eps);
+        values_eps.as_slice_mut().unwrap()[i] = values.as_slice().unwrap()[i];
+        value
+    })
+}
+
+// (f(v + eps) - f(v - eps)) / 2 eps for every one of `len` values, `f(i, delta)` evaluates with value i moved by delta
+fn central_differences<O: FnMut(usize, f32) -> f64>(len: usize, eps: f32, mut f: O) -> Array1<f64> {
+    (0..len)
+        .map(|i| (f(i, eps) - f(i, -eps)) / (2.0 * eps as f64))
+        .collect()
+}
+
+fn max_relative_error<'a, F, A, N>(analytic: A, numeric: N) -> f32
+where
+    F: Float,
+    A: Iterator<Item = &'a F>,
+    N: Iterator<Item = &'a f64>,
+{
+    analytic
+        .zip(numeric)
+        .map(|(&a, &n)| {
+            let a = a.into_f64();
+            ((a - n).abs() / a.abs().max(n.abs()).max(ERROR_FLOOR)) as f32
+        })
+        .fold(0.0, f32::max)
+}
+
+// weights first, then bias, like `flatten_params`
+fn set_param<F: Float, P: Propagate<F>>(layer: &mut P, i: usize, num_weights: usize, value: F) {
+    let (weights, _, bias, _) = layer.params_grads().unwrap();
+    if i < num_weights {
+        *weights.iter_mut().nth(i).unwrap() = value;
+    } else {
+        bias[i - num_weights] = value;
+    }
+}
+
+// fixed pseudo random weights of the checked scalar, so no output gets a zero gradient
+fn projection<F: Float>(dim: (usize, usize)) -> Array2<F> {
+    Array2::from_shape_fn(dim, |(i, j)| F::from_f32(((i * dim.1 + j) as f32 * 1.618 + 0.5).sin()))
+}
@@ -1,65 +1,131 @@
-use ndarray::{Array1, Array2, Axis};
+use std::error::Error;
+
+use ndarray::{concatenate, Array1, Array2, Axis};
+use rayon::prelude::*;
 
 pub use layers::*;
 pub use loss::*;
 pub use optimizer::*;
 pub use savemodel::*;
 pub use loadmodel::*;
+pub use weightaverage::{Averaging, WeightAverage};
+pub use flatparams::*;
+pub use crossvalidation::{cross_validate, CrossValidation, Metric};
+pub use metrics::*;
+pub use probmetrics::*;
+pub use builder::NNBuilder;
+pub use summary::{LayerSummary, ModelSummary};
+pub use gradcheck::{gradcheck, gradcheck_loss, gradcheck_model, GradCheck};
 
-pub struct NN<S: Loss, O: Optimizer> {
-    pub layers: Vec<LayerTypes>,
+// `F` is the element type of layers, loss and optimizer, see `Float`
+pub struct NN<S: Loss<F>, O, F: Float = f32> {
+    pub layers: Vec<LayerTypes<F>>,
     pub loss_fn: S,
     pub optim: O,
-    pub regularization: Regularization,
 }
 
-impl<S: Loss, O: Optimizer> NN<S, O> {
-    pub fn compute_confusion_matrix(y_true: &Array1<usize>, y_pred: &Array1<usize>, num_classes: usize) -> Array2<usize> {
-        let mut cm = Array2::<usize>::zeros((num_classes, num_classes));
-        for (&t, &p) in y_true.iter().zip(y_pred.iter()) {
-            cm[[t, p]] += 1;
+// `predict` and `predict_proba` take `&self`, so a trained model can serve several threads
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    const fn assert_nn<F: Float, S: Loss<F> + Send + Sync, O: Send + Sync>() {
+        assert_send_sync::<NN<S, O, F>>();
+    }
+    assert_nn::<f32, CrossEntropyLoss, AdamOptimizer>();
+};
+
+impl<F: Float, S: Loss<F>, O> NN<S, O, F> {
+    pub fn compute_confusion_matrix(y_true: &Array1<usize>, y_pred: &Array1<usize>, num_classes: usize) -> Result<Array2<usize>, Box<dyn Error>> {
+        confusion_matrix(y_true, y_pred, num_classes)
+    }
+
+    pub fn compute_metrics(cm: &Array2<usize>, zero_division: ZeroDivision) -> Vec<ClassMetrics> {
+        class_metrics(cm, zero_division)
+    }
+
+    // Freeze layer at `idx` in `layers`, its weights are excluded from optimizer updates
+    pub fn freeze(&mut self, idx: usize) {
+        self.layers[idx].set_frozen(true);
+    }
+
+    pub fn unfreeze(&mut self, idx: usize) {
+        self.layers[idx].set_frozen(false);
+    }
+
+    pub fn is_frozen(&self, idx: usize) -> bool {
+        self.layers[idx].is_frozen()
+    }
+
+    // Freeze every layer before `idx`, useful to fine-tune only the last layers
+    pub fn freeze_until(&mut self, idx: usize) {
+        for (i, layer) in self.layers.iter_mut().enumerate() {
+            layer.set_frozen(i < idx);
         }
-        cm
     }
 
-    pub fn compute_metrics(cm: &Array2<usize>) {
-        let num_classes = cm.nrows();
+    // Sum gradients over several backward passes instead of overwriting them.
+    // Only `accumulate_step` builds on earlier gradients, the other training steps zero them first.
+    // Switching it on zeroes gradients, so the first micro-batch doesn't add onto those of an earlier step.
+    pub fn set_accumulate_grads(&mut self, accumulate: bool) {
+        if accumulate && !self.is_accumulating() {
+            self.zero_grad();
+        }
+        for layer in self.layers.iter_mut() {
+            layer.set_accumulate(accumulate);
+        }
+    }
 
-        for class in 0..num_classes {
-            let tpos = cm[[class, class]];
-            let fpos = cm.column(class).sum() - tpos;
-            let fneg = cm.row(class).sum() - tpos;
-            let tneg = cm.sum() - (tpos + fpos + fneg);
-            let precision: f32 = tpos as f32 / (tpos + fpos) as f32;
-            let recall: f32 = tpos as f32 / (tpos + fneg) as f32;
+    pub fn is_accumulating(&self) -> bool {
+        self.layers.iter().any(|l| l.is_accumulating())
+    }
 
-            println!("Class {}: TP = {}, FP = {}, FN = {}, TN = {}", class, tpos, fpos, fneg, tneg);
-            println!("Precision = {}", precision);
-            println!("Recall(Sensitivity) = {}", recall);
-            println!("F1-score: = {}",  2.0 * (precision * recall) / (precision + recall));
-            println!("---------------------------------------------");
+    pub fn zero_grad(&mut self) {
+        for layer in self.layers.iter_mut() {
+            layer.zero_grad();
         }
     }
 
+    // Same layers in another precision with a fresh loss and optimizer, e.g. a model trained
+    // in f32 stored as f16 or bf16 for inference, or an f32 model fine-tuned in f64.
+    // Fails if an autograd layer's block can't compute in `G`, see `AutogradLayer::cast`.
+    pub fn cast<G: Float, S2: Loss<G>, O2>(&self, loss_fn: S2, optim: O2) -> Result<NN<S2, O2, G>, Box<dyn Error>> {
+        Ok(NN {
+            layers: self.layers.iter().map(|l| l.cast()).collect::<Result<_, _>>()?,
+            loss_fn,
+            optim,
+        })
+    }
+
     // Forward pass through all layers.
-    pub fn forward_all(&mut self, x: &Array2<f32>) -> Array2<f32> {
-        let mut z = x.clone();
-        for layer in  self.layers.iter_mut(){
-            z = layer.forward(&z);
-        }
-        z
+    pub fn forward_all(&mut self, x: &Array2<F>) -> Array2<F> {
+        forward_layers(&mut self.layers, x)
+    }
+
+    // Class probabilities of shape (batch size, number of classes).
+    // Takes `&self`, a trained model can serve requests from several threads at once.
+    pub fn predict_proba(&self, x: &Array2<F>) -> Array2<F> {
+        let logits = self.layers.iter().fold(x.clone(), |z, layer| layer.infer(&z));
+        Softmax::new().infer(&logits)
+    }
+
+    // Most probable class of every row
+    pub fn predict(&self, x: &Array2<F>) -> Array1<usize> {
+        self.predict_proba(x)
+            .axis_iter(Axis(0))
+            .map(argmax)
+            .collect()
     }
 
     // Backward pass: loss gradient → all layers (in reverse)
-    pub fn backward_all(&mut self, grad_loss: &Array2<f32>) {
-        let mut grad = grad_loss.clone();
-        for layer in self.layers.iter_mut().rev() {
-            grad = layer.backward(&grad);
-        }
+    pub fn backward_all(&mut self, grad_loss: &Array2<F>) {
+        backward_layers(&mut self.layers, grad_loss);
     }
 
     // Single training step on batch (inputs, targets)
-    pub fn train_step(&mut self, x: &Array2<f32>, y: &Array2<f32>, itertation: usize, save_at: usize, save_path: &str) -> (f32, f32) {
+    pub fn train_step(&mut self, x: &Array2<F>, y: &Array2<F>, itertation: usize, save_at: usize, save_path: &str) -> (F, f32)
+    where
+        O: Optimizer<F>,
+    {
+        self.zero_grad();
         // Forward
         let preds = self.forward_all(x);
         // Probabilities
@@ -67,16 +133,10 @@ impl<S: Loss, O: Optimizer> NN<S, O> {
         // --------------------Prediction Labels--------------------
         let pred_labels: Array1<usize> = probs
             .axis_iter(Axis(0))
-            .map(|row| {
-                row.iter()
-                    .enumerate()
-                    .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
-                    .unwrap()
-                    .0
-            })
+            .map(argmax)
             .collect();
 
-        let target_labels: Array1<usize> = y.iter().map(|x| *x as usize).collect();
+        let target_labels: Array1<usize> = y.iter().map(|x| x.into_f32() as usize).collect();
 
         // --------------------Accuracy--------------------
         let correct = pred_labels
@@ -89,56 +149,24 @@ impl<S: Loss, O: Optimizer> NN<S, O> {
         // Loss
         let loss = self.loss_fn.forward(&preds, y);
 
-        // Add regularization penalty
-        let mut reg_penalty = 0.0;
-        for layer in self.layers.iter_mut() {
-            if let Some((weights, _, _, _)) = layer.params_grads() {
-                match self.regularization {
-                    Regularization::L1{lambda} => {
-                        reg_penalty += lambda * weights.mapv(|x| x.abs()).sum();
-                    }
-                    Regularization::L2{lambda} => {
-                        reg_penalty += lambda * weights.mapv(|w| w * w).sum();
-                    }
-                    Regularization::ElasticNet { l1, l2 } => {
-                        let l1_term = l1 * weights.mapv(f32::abs).sum();
-                        let l2_term = l2 * weights.mapv(|w| w * w).sum();
-                        reg_penalty += l1_term + l2_term;
-                    }
-                    Regularization::None => {}
-                }
-            }
-        }
+        // Add regularization penalty of every layer
+        let reg_penalty = regularization_penalty(&self.layers);
 
         let final_loss = loss + reg_penalty;
 
         let grad_loss = self.loss_fn.backward(&preds);
         // Backward
         self.backward_all(&grad_loss);
-        // Collect all params and grads
-        let mut all_weights = Vec::new();
-        let mut all_grad_weights = Vec::new();
-        let mut all_bias = Vec::new();
-        let mut all_grad_bias = Vec::new();
-        for layer in self.layers.iter_mut() {
-            match layer.params_grads() {
-                Some((weights ,grad_weights, bias, grad_bias)) => {
-                    all_weights.push(weights);
-                    all_grad_weights.push(grad_weights);
-                    all_bias.push(bias);
-                    all_grad_bias.push(grad_bias);
-                }
-                None => { 
-                    // println!("Activation Layer weights and params can't be collected") 
-                }
-            }
-        }
         // Update
-        self.optim.step_weight(&mut all_weights, &mut all_grad_weights);
-        self.optim.step_bias(&mut all_bias, &mut all_grad_bias);
+        self.optimizer_step();
 
-        // save the last weights and bias
+        // save the last weights and bias, frozen layers included
         if itertation == save_at {
+            let (all_weights, all_bias): (Vec<_>, Vec<_>) = self.layers
+                .iter_mut()
+                .filter_map(|l| l.params_grads())
+                .map(|(weights, _, bias, _)| (weights, bias))
+                .unzip();
             if let Err(e) = save_model(all_weights, all_bias, save_path) {
                 eprintln!("Failed to save weights: {}", e);
             }
@@ -147,25 +175,202 @@ impl<S: Loss, O: Optimizer> NN<S, O> {
         (final_loss, accuracy)
     }
 
-    pub fn test_step(&mut self, x: &Array2<f32>, y: &Array2<f32>) -> (f32, f32) {
+    // Training step for optimizers that re-evaluate the loss (e.g. L-BFGS line search).
+    // Every evaluation re-runs forward, loss and backward on the full batch (inputs, targets).
+    pub fn train_step_closure(&mut self, x: &Array2<F>, y: &Array2<F>) -> (F, f32)
+    where
+        O: ClosureOptimizer<F>,
+    {
+        let mut params = flatten_params(&mut self.layers);
+        let layers = &mut self.layers;
+        let loss_fn = &mut self.loss_fn;
+        let mut last_preds = None;
+
+        let mut closure = |p: &Array1<F>| {
+            unflatten_params(layers, p);
+            let preds = forward_layers(layers, x);
+            let loss = loss_fn.forward(&preds, y) + regularization_penalty(layers);
+            let grad_loss = loss_fn.backward(&preds);
+            layers.iter_mut().for_each(|l| l.zero_grad());
+            backward_layers(layers, &grad_loss);
+            last_preds = Some(preds);
+            (loss, flatten_grads(layers))
+        };
+        let loss = self.optim.step_closure(&mut params, &mut closure);
+        unflatten_params(&mut self.layers, &params);
+        self.apply_constraints();
+
+        // the last evaluation is always at the accepted params
+        let preds = last_preds.expect("closure was never evaluated");
+        let accuracy = accuracy(&preds, y);
+
+        (loss, accuracy)
+    }
+
+    // Forward and backward on one micro-batch without updating weights.
+    // Turns on `set_accumulate_grads` if it is off, so gradients add up until `step_accumulated`.
+    pub fn accumulate_step(&mut self, x: &Array2<F>, y: &Array2<F>) -> (F, f32) {
+        self.set_accumulate_grads(true);
+        let preds = self.forward_all(x);
+        let accuracy = accuracy(&preds, y);
+        let loss = self.loss_fn.forward(&preds, y) + regularization_penalty(&self.layers);
+        let grad_loss = self.loss_fn.backward(&preds);
+        self.backward_all(&grad_loss);
+
+        (loss, accuracy)
+    }
+
+    // Average gradients summed over `num_micro_batches` calls of `accumulate_step`,
+    // update weights with the optimizer and zero gradients for the next accumulation
+    pub fn step_accumulated(&mut self, num_micro_batches: usize)
+    where
+        O: Optimizer<F>,
+    {
+        assert!(num_micro_batches > 0, "step_accumulated needs at least one micro-batch");
+        let scale = F::from_f32(1.0 / num_micro_batches as f32);
+        for layer in self.layers.iter_mut().filter(|l| !l.is_frozen()) {
+            if let Some((_, grad_weights, _, grad_bias)) = layer.params_grads() {
+                grad_weights.mapv_inplace(|g| g * scale);
+                grad_bias.mapv_inplace(|g| g * scale);
+            }
+        }
+        self.optimizer_step();
+        self.zero_grad();
+    }
+
+    // Sharpness-aware minimization step: gradient at the worst-case weights within an L2 ball of radius `rho`
+    // is applied by the configured optimizer to the original weights.
+    pub fn train_step_sam(&mut self, x: &Array2<F>, y: &Array2<F>, rho: f32) -> (F, f32)
+    where
+        O: Optimizer<F>,
+    {
+        self.zero_grad();
+        // First pass at the current weights
+        let preds = self.forward_all(x);
+        let accuracy = accuracy(&preds, y);
+        let loss = self.loss_fn.forward(&preds, y) + regularization_penalty(&self.layers);
+        let grad_loss = self.loss_fn.backward(&preds);
+        self.backward_all(&grad_loss);
+
+        // Perturb weights along the normalized gradient, e = rho * g / ||g||
+        let squares = flatten_grads(&mut self.layers).mapv(|g| g * g).insert_axis(Axis(0));
+        let grad_norm = F::sum_all(&squares).sqrt();
+        let scale = F::from_f32(rho) / (grad_norm + F::from_f32(1e-12).max(F::min_positive_value()));
+        let mut perturbations = Vec::new();
+        for layer in self.layers.iter_mut().filter(|l| !l.is_frozen()) {
+            if let Some((weights, grad_weights, bias, grad_bias)) = layer.params_grads() {
+                let e_w = grad_weights.mapv(|g| scale * g);
+                let e_b = grad_bias.mapv(|g| scale * g);
+                *weights += &e_w;
+                *bias += &e_b;
+                perturbations.push((e_w, e_b));
+            }
+        }
+
+        // Second pass at the perturbed weights
+        let preds = self.forward_all(x);
+        self.loss_fn.forward(&preds, y);
+        let grad_loss = self.loss_fn.backward(&preds);
+        self.zero_grad();
+        self.backward_all(&grad_loss);
+
+        // Restore weights and update them with the sharpness-aware gradient
+        let params = self.layers
+            .iter_mut()
+            .filter(|l| !l.is_frozen())
+            .filter_map(|l| l.params_grads());
+        for ((weights, _, bias, _), (e_w, e_b)) in params.zip(perturbations.iter()) {
+            *weights -= e_w;
+            *bias -= e_b;
+        }
+        self.optimizer_step();
+
+        (loss, accuracy)
+    }
+
+    // Data-parallel training step: the batch is split into `num_workers` row chunks whose forward
+    // and backward passes run on rayon threads, the first on the model's layers and the others on
+    // replicas. The loss sees the whole batch, the gradients of the replicas are summed into the
+    // model and one optimizer step is applied, so it matches `train_step` up to float rounding.
+    pub fn train_step_parallel(&mut self, x: &Array2<F>, y: &Array2<F>, num_workers: usize) -> Result<(F, f32), Box<dyn Error>>
+    where
+        O: Optimizer<F>,
+    {
+        if x.nrows() == 0 {
+            return Err("train_step_parallel needs at least one row".into());
+        }
+        if x.nrows() != y.nrows() {
+            return Err(format!("{} input rows but {} target rows", x.nrows(), y.nrows()).into());
+        }
+        self.zero_grad();
+        // at most one chunk per row, so more workers than rows leave the extra workers idle
+        let chunk_rows = x.nrows().div_ceil(num_workers.max(1)).max(1);
+        let x_chunks: Vec<_> = x.axis_chunks_iter(Axis(0), chunk_rows).collect();
+        let mut replicas: Vec<Vec<LayerTypes<F>>> = (1..x_chunks.len())
+            .map(|_| self.layers.iter().map(|l| l.replica()).collect())
+            .collect();
+        // weights do not change until the optimizer step
+        let penalty = regularization_penalty(&self.layers);
+
+        let mut workers: Vec<&mut [LayerTypes<F>]> = std::iter::once(&mut self.layers[..])
+            .chain(replicas.iter_mut().map(|r| &mut r[..]))
+            .collect();
+        let preds: Vec<Array2<F>> = workers
+            .par_iter_mut()
+            .zip(x_chunks.par_iter())
+            .map(|(layers, x)| forward_layers(layers, &x.to_owned()))
+            .collect();
+        let preds = concatenate(Axis(0), &preds.iter().map(|p| p.view()).collect::<Vec<_>>())?;
+
+        let accuracy = accuracy(&preds, y);
+        let loss = self.loss_fn.forward(&preds, y) + penalty;
+        let grad_loss = self.loss_fn.backward(&preds);
+        let grad_chunks: Vec<_> = grad_loss.axis_chunks_iter(Axis(0), chunk_rows).collect();
+        workers
+            .par_iter_mut()
+            .zip(grad_chunks.par_iter())
+            .for_each(|(layers, grad)| backward_layers(layers, &grad.to_owned()));
+
+        // Reduce
+        for replica in replicas.iter_mut() {
+            for (layer, copy) in self.layers.iter_mut().zip(replica.iter_mut()).filter(|(l, _)| !l.is_frozen()) {
+                if let (Some((_, grad_weights, _, grad_bias)), Some((_, copy_grad_weights, _, copy_grad_bias))) = (layer.params_grads(), copy.params_grads()) {
+                    *grad_weights += &*copy_grad_weights;
+                    *grad_bias += &*copy_grad_bias;
+                }
+            }
+        }
+        self.optimizer_step();
+
+        Ok((loss, accuracy))
+    }
+
+    // Evaluate on batch (inputs, targets), print the result with `println!("{report}")`.
+    // Metrics with a zero denominator are reported as 0 and calibration uses 10 bins, see `test_step_with`.
+    pub fn test_step(&mut self, x: &Array2<F>, y: &Array2<F>) -> Result<EvaluationReport, Box<dyn Error>> {
+        self.test_step_with(x, y, ZeroDivision::Zero, 10)
+    }
+
+    // `test_step` with the value of metrics with a zero denominator and the number of calibration bins
+    pub fn test_step_with(&mut self, x: &Array2<F>, y: &Array2<F>, zero_division: ZeroDivision, num_bins: usize) -> Result<EvaluationReport, Box<dyn Error>> {
+        if num_bins == 0 {
+            return Err("calibration needs at least one bin".into());
+        }
         let preds = self.forward_all(x);
         let probs = Softmax::new().forward(&preds);
-        let loss = self.loss_fn.forward(&preds, y);
-        
+        // labels are checked before the loss indexes into predictions with them
+        let target_labels = target_labels(y)?;
+        if let Some(&label) = target_labels.iter().find(|&&t| t >= preds.ncols()) {
+            return Err(format!("target label {} is out of range for {} classes", label, preds.ncols()).into());
+        }
+        let loss = self.loss_fn.forward(&preds, y).into_f32();
+
         // --------------------Prediction Labels--------------------
         let pred_labels: Array1<usize> = probs
             .axis_iter(Axis(0))
-            .map(|row| {
-                row.iter()
-                    .enumerate()
-                    .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
-                    .unwrap()
-                    .0
-            })
+            .map(argmax)
             .collect();
 
-        let target_labels: Array1<usize> = y.iter().map(|x| *x as usize).collect();
-
         // --------------------Accuracy--------------------
         let correct = pred_labels
             .iter()
@@ -177,13 +382,93 @@ impl<S: Loss, O: Optimizer> NN<S, O> {
 
         // --------------------Confusion Matrix & Metrics--------------------
         let num_classes = probs.shape()[1];
-        let cm = Self::compute_confusion_matrix(&target_labels, &pred_labels, num_classes);
-        println!("Confusion Matrix:\n{cm}");
-        Self::compute_metrics(&cm);
+        let cm = Self::compute_confusion_matrix(&target_labels, &pred_labels, num_classes)?;
 
-        (loss, accuracy)
+        Ok(EvaluationReport::new(loss, accuracy, cm, zero_division).with_probabilities(&probs.mapv(F::into_f32), &target_labels, num_bins))
     }
 }
 
+impl<F: Float, S: Loss<F>, O> NN<S, O, F> {
+    // Hand every trainable layer to the optimizer, frozen ones without gradient,
+    // so optimizer state stays with its layer when the frozen set changes
+    fn optimizer_step(&mut self)
+    where
+        O: Optimizer<F>,
+    {
+        let mut all_weights = Vec::new();
+        let mut all_grad_weights = Vec::new();
+        let mut all_bias = Vec::new();
+        let mut all_grad_bias = Vec::new();
+        for layer in self.layers.iter_mut() {
+            let frozen = layer.is_frozen();
+            match layer.params_grads() {
+                Some((weights ,grad_weights, bias, grad_bias)) => {
+                    all_weights.push(weights);
+                    all_grad_weights.push((!frozen).then_some(grad_weights));
+                    all_bias.push(bias);
+                    all_grad_bias.push((!frozen).then_some(grad_bias));
+                }
+                None => { 
+                    // println!("Activation Layer weights and params can't be collected") 
+                }
+            }
+        }
+        self.optim.step_weight(&mut all_weights, &mut all_grad_weights);
+        self.optim.step_bias(&mut all_bias, &mut all_grad_bias);
+        self.apply_constraints();
+    }
+
+    fn apply_constraints(&mut self) {
+        for layer in self.layers.iter_mut().filter(|l| !l.is_frozen()) {
+            layer.apply_constraint();
+        }
+    }
+}
+
+// Percentage of rows whose argmax matches the target label
+pub fn accuracy<F: Float>(preds: &Array2<F>, y: &Array2<F>) -> f32 {
+    let correct = preds
+        .axis_iter(Axis(0))
+        .map(argmax)
+        .zip(y.iter())
+        .filter(|(p, t)| *p == t.into_f32() as usize)
+        .count();
+    (correct as f32 / y.len() as f32) * 100.0
+}
+
+fn forward_layers<F: Float>(layers: &mut [LayerTypes<F>], x: &Array2<F>) -> Array2<F> {
+    let mut z = x.clone();
+    for layer in layers.iter_mut() {
+        z = layer.forward(&z);
+    }
+    z
+}
+
+fn backward_layers<F: Float>(layers: &mut [LayerTypes<F>], grad_loss: &Array2<F>) {
+    // layers before the first trainable one need no gradient at all
+    let first_trainable = layers
+        .iter_mut()
+        .position(|l| !l.is_frozen() && l.params_grads().is_some())
+        .unwrap_or(layers.len());
+
+    let mut grad = grad_loss.clone();
+    for layer in layers[first_trainable..].iter_mut().rev() {
+        grad = layer.backward(&grad);
+    }
+}
+
+// Sum of the penalties every layer adds to the loss
+fn regularization_penalty<F: Float>(layers: &[LayerTypes<F>]) -> F {
+    layers.iter().fold(F::zero(), |sum, l| sum + l.penalty())
+}
+
 pub mod savemodel;
-pub mod loadmodel;
\ No newline at end of file
+pub mod loadmodel;
+pub mod weightaverage;
+pub mod flatparams;
+pub mod crossvalidation;
+pub mod metrics;
+pub mod probmetrics;
+pub mod builder;
+pub mod summary;
+pub mod gradcheck;
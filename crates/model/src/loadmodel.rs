@@ -1,49 +1,55 @@
 use std::error::Error;
 
+use std::mem::size_of;
+
 use ndarray::{Array1, Array2};
 use polars::prelude::*;
 
-pub fn load_array2_from_csv(save_path: &str) -> Result<Array2<f32>, Box<dyn Error>> {
+use super::Float;
+
+pub fn load_array2_from_csv<F: Float>(save_path: &str) -> Result<Array2<F>, Box<dyn Error>> {
     let lf = LazyCsvReader::new(save_path)
         .with_has_header(true)
         .finish()?;
     
     let df = lf
         .with_streaming(true)
-        .cast_all(DataType::Float32, true)
+        .cast_all(dtype::<F>(), true)
         .collect()?;
 
-    let cols = df.get_columns();
+    let cols = df.get_columns().iter().map(values).collect::<Result<Vec<Vec<F>>, _>>()?;
 
     let height = cols[0].len();
     let width = cols.len();
 
     let mut data = Vec::with_capacity(height * width);
     for row in 0..height {
-        for col in 0..width {
-            data.push(cols[col].f32()?.get(row).unwrap());
+        for col in cols.iter().take(width) {
+            data.push(col[row]);
         }
     }
 
     Ok(Array2::from_shape_vec((height, width), data)?)
 }
 
-pub fn load_array1_from_csv(save_path: &str) -> Result<Array1<f32>, Box<dyn Error>> {
+pub fn load_array1_from_csv<F: Float>(save_path: &str) -> Result<Array1<F>, Box<dyn Error>> {
     let lf = LazyCsvReader::new(save_path)
         .with_has_header(true)
         .finish()?;
     
     let df = lf
         .with_streaming(true)
-        .cast_all(DataType::Float32, true)
+        .cast_all(dtype::<F>(), true)
         .collect()?;
 
     let col = &df.get_columns()[0];
-    let data = col.f32()?.into_no_null_iter().collect::<Vec<_>>();
-    Ok(Array1::from(data))
+    Ok(Array1::from(values(col)?))
 }
 
-pub fn load_model_weights_and_biases(save_path: &str, num_hidden_layers: usize) -> Result<(Vec<Array2<f32>>, Vec<Array1<f32>>), Box<dyn Error>> {
+// all weights, all biases
+pub type ModelParams<F = f32> = (Vec<Array2<F>>, Vec<Array1<F>>);
+
+pub fn load_model_weights_and_biases<F: Float>(save_path: &str, num_hidden_layers: usize) -> Result<ModelParams<F>, Box<dyn Error>> {
     let mut all_weights = Vec::new();
     let mut all_bias = Vec::new();
 
@@ -59,4 +65,17 @@ pub fn load_model_weights_and_biases(save_path: &str, num_hidden_layers: usize)
     }
 
     Ok((all_weights, all_bias))
-}
\ No newline at end of file
+}
+
+// read as f64 for f64 models, as f32 otherwise, like `save_model` writes them
+fn dtype<F: Float>() -> DataType {
+    if size_of::<F>() > size_of::<f32>() { DataType::Float64 } else { DataType::Float32 }
+}
+
+fn values<F: Float>(col: &Column) -> PolarsResult<Vec<F>> {
+    if size_of::<F>() > size_of::<f32>() {
+        Ok(col.f64()?.into_no_null_iter().map(F::from_f64).collect())
+    } else {
+        Ok(col.f32()?.into_no_null_iter().map(F::from_f32).collect())
+    }
+}
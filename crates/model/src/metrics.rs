@@ -0,0 +1,188 @@
+use std::error::Error;
+use std::fmt;
+
+use ndarray::{Array1, Array2};
+use serde::{Serialize, Serializer};
+
+use super::{Float, ProbabilityMetrics};
+
+// Value of a metric whose denominator is zero, e.g. precision of a class that is never predicted
+// or recall of a class without targets
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub enum ZeroDivision {
+    #[default]
+    Zero,
+    One,
+    // NaN classes are left out of the macro and weighted averages
+    NaN,
+}
+
+impl ZeroDivision {
+    pub fn value(self) -> f32 {
+        match self {
+            ZeroDivision::Zero => 0.0,
+            ZeroDivision::One => 1.0,
+            ZeroDivision::NaN => f32::NAN,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClassMetrics {
+    pub precision: f32,
+    pub recall: f32,
+    pub f1: f32,
+    // number of targets of the class
+    pub support: usize,
+}
+
+// Result of `test_step`, can be printed with `{}` or serialized with `to_json`
+#[derive(Debug, Clone, Serialize)]
+pub struct EvaluationReport {
+    pub loss: f32,
+    // percentage of correct predictions
+    pub accuracy: f32,
+    // rows are targets, columns are predictions
+    #[serde(serialize_with = "serialize_rows")]
+    pub confusion_matrix: Array2<usize>,
+    pub per_class: Vec<ClassMetrics>,
+    pub macro_avg: ClassMetrics,
+    pub micro_avg: ClassMetrics,
+    pub weighted_avg: ClassMetrics,
+    pub zero_division: ZeroDivision,
+    // set by `with_probabilities`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub probability_metrics: Option<ProbabilityMetrics>,
+}
+
+impl EvaluationReport {
+    pub fn new(loss: f32, accuracy: f32, confusion_matrix: Array2<usize>, zero_division: ZeroDivision) -> Self {
+        let per_class = class_metrics(&confusion_matrix, zero_division);
+        let total: usize = confusion_matrix.sum();
+
+        // weighted mean over classes whose value is defined
+        let average = |weight: fn(&ClassMetrics) -> f32| {
+            let mean = |value: fn(&ClassMetrics) -> f32| {
+                let (sum, weights) = per_class
+                    .iter()
+                    .filter(|m| !value(m).is_nan())
+                    .fold((0.0, 0.0), |(sum, weights), m| (sum + weight(m) * value(m), weights + weight(m)));
+                if weights > 0.0 { sum / weights } else { zero_division.value() }
+            };
+            ClassMetrics {
+                precision: mean(|m| m.precision),
+                recall: mean(|m| m.recall),
+                f1: mean(|m| m.f1),
+                support: total,
+            }
+        };
+        let macro_avg = average(|_| 1.0);
+        let weighted_avg = average(|m| m.support as f32);
+
+        // every wrong prediction is a false positive of one class and a false negative of another
+        let correct: usize = confusion_matrix.diag().sum();
+        let micro = divide(correct, total, zero_division);
+        let micro_avg = ClassMetrics { precision: micro, recall: micro, f1: micro, support: total };
+
+        EvaluationReport { loss, accuracy, confusion_matrix, per_class, macro_avg, micro_avg, weighted_avg, zero_division, probability_metrics: None }
+    }
+
+    // Adds ROC-AUC, average precision, log-loss, Brier score, top-k accuracy and calibration
+    // from softmax probabilities, with `num_bins` reliability diagram bins
+    pub fn with_probabilities(mut self, probs: &Array2<f32>, labels: &Array1<usize>, num_bins: usize) -> Self {
+        self.probability_metrics = Some(ProbabilityMetrics::new(probs, labels, num_bins));
+        self
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl fmt::Display for EvaluationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Confusion Matrix:\n{}", self.confusion_matrix)?;
+        writeln!(f, "{:>12} {:>10} {:>10} {:>10} {:>10}", "", "precision", "recall", "f1-score", "support")?;
+        for (class, m) in self.per_class.iter().enumerate() {
+            writeln!(f, "{:>12} {:>10.4} {:>10.4} {:>10.4} {:>10}", class, m.precision, m.recall, m.f1, m.support)?;
+        }
+        for (name, m) in [("macro avg", &self.macro_avg), ("micro avg", &self.micro_avg), ("weighted avg", &self.weighted_avg)] {
+            writeln!(f, "{:>12} {:>10.4} {:>10.4} {:>10.4} {:>10}", name, m.precision, m.recall, m.f1, m.support)?;
+        }
+        writeln!(f, "Loss: {}", self.loss)?;
+        write!(f, "Accuracy: {}%", self.accuracy)?;
+        if let Some(probability_metrics) = &self.probability_metrics {
+            write!(f, "\n{}", probability_metrics)?;
+        }
+        Ok(())
+    }
+}
+
+// confusion matrix as a list of rows in JSON
+fn serialize_rows<S: Serializer>(cm: &Array2<usize>, serializer: S) -> Result<S::Ok, S::Error> {
+    let rows: Vec<Vec<usize>> = cm.outer_iter().map(|row| row.to_vec()).collect();
+    rows.serialize(serializer)
+}
+
+// Targets of shape (batch size, 1) as class labels, they must be non-negative integers
+pub fn target_labels<F: Float>(y: &Array2<F>) -> Result<Array1<usize>, Box<dyn Error>> {
+    y.iter()
+        .enumerate()
+        .map(|(row, &label)| {
+            let label = label.into_f64();
+            if label.is_finite() && label >= 0.0 && label.fract() == 0.0 {
+                Ok(label as usize)
+            } else {
+                Err(format!("target {} at row {} is not a class label", label, row).into())
+            }
+        })
+        .collect()
+}
+
+// Number of classes covering every label of targets and predictions
+pub fn num_classes(y_true: &Array1<usize>, y_pred: &Array1<usize>) -> usize {
+    y_true.iter().chain(y_pred.iter()).max().map_or(0, |&max| max + 1)
+}
+
+pub fn confusion_matrix(y_true: &Array1<usize>, y_pred: &Array1<usize>, num_classes: usize) -> Result<Array2<usize>, Box<dyn Error>> {
+    if y_true.len() != y_pred.len() {
+        return Err(format!("{} targets but {} predictions", y_true.len(), y_pred.len()).into());
+    }
+    let mut cm = Array2::<usize>::zeros((num_classes, num_classes));
+    for (row, (&t, &p)) in y_true.iter().zip(y_pred.iter()).enumerate() {
+        if t >= num_classes {
+            return Err(format!("target label {} at row {} is out of range for {} classes", t, row, num_classes).into());
+        }
+        if p >= num_classes {
+            return Err(format!("predicted label {} at row {} is out of range for {} classes", p, row, num_classes).into());
+        }
+        cm[[t, p]] += 1;
+    }
+    Ok(cm)
+}
+
+// Precision, recall and F1 of every class from a confusion matrix
+pub fn class_metrics(cm: &Array2<usize>, zero_division: ZeroDivision) -> Vec<ClassMetrics> {
+    (0..cm.nrows())
+        .map(|class| {
+            let tpos = cm[[class, class]];
+            let fpos = cm.column(class).sum() - tpos;
+            let fneg = cm.row(class).sum() - tpos;
+            ClassMetrics {
+                precision: divide(tpos, tpos + fpos, zero_division),
+                recall: divide(tpos, tpos + fneg, zero_division),
+                // same as the harmonic mean of precision and recall whenever both are defined
+                f1: divide(2 * tpos, 2 * tpos + fpos + fneg, zero_division),
+                support: tpos + fneg,
+            }
+        })
+        .collect()
+}
+
+fn divide(numerator: usize, denominator: usize, zero_division: ZeroDivision) -> f32 {
+    if denominator == 0 {
+        zero_division.value()
+    } else {
+        numerator as f32 / denominator as f32
+    }
+}
@@ -0,0 +1,301 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use ndarray::{Array1, Array2, ArrayView1, Axis};
+use serde::Serialize;
+
+use super::Float;
+
+// Metrics from softmax probabilities of shape (batch size, number of classes) and target labels.
+// One-vs-rest AUC and average precision are NaN for classes without positives or negatives
+// and left out of the macro averages, which are None when no class has both.
+// NaN probabilities rank below every number, see `cmp_probability`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbabilityMetrics {
+    pub roc_auc: Vec<f32>,
+    pub macro_roc_auc: Option<f32>,
+    pub average_precision: Vec<f32>,
+    pub macro_average_precision: Option<f32>,
+    pub log_loss: f32,
+    pub brier_score: f32,
+    // top_k_accuracy[i] is the top-(i + 1) accuracy in percent
+    pub top_k_accuracy: Vec<f32>,
+    pub calibration: Calibration,
+}
+
+impl ProbabilityMetrics {
+    pub fn new(probs: &Array2<f32>, labels: &Array1<usize>, num_bins: usize) -> Self {
+        let roc_auc = roc_auc(probs, labels);
+        let average_precision = average_precision(probs, labels);
+        ProbabilityMetrics {
+            macro_roc_auc: nan_mean(&roc_auc),
+            roc_auc,
+            macro_average_precision: nan_mean(&average_precision),
+            average_precision,
+            log_loss: log_loss(probs, labels),
+            brier_score: brier_score(probs, labels),
+            top_k_accuracy: (1..=probs.ncols().min(5)).map(|k| top_k_accuracy(probs, labels, k)).collect(),
+            calibration: calibration(probs, labels, num_bins),
+        }
+    }
+}
+
+impl fmt::Display for ProbabilityMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:>12} {:>10} {:>10}", "", "roc-auc", "avg-prec")?;
+        for (class, (auc, ap)) in self.roc_auc.iter().zip(self.average_precision.iter()).enumerate() {
+            writeln!(f, "{:>12} {:>10.4} {:>10.4}", class, auc, ap)?;
+        }
+        let average = |value: Option<f32>| value.map_or("undefined".to_string(), |v| format!("{:.4}", v));
+        writeln!(f, "{:>12} {:>10} {:>10}", "macro avg", average(self.macro_roc_auc), average(self.macro_average_precision))?;
+        writeln!(f, "Log-loss: {}", self.log_loss)?;
+        writeln!(f, "Brier score: {}", self.brier_score)?;
+        for (k, accuracy) in self.top_k_accuracy.iter().enumerate() {
+            writeln!(f, "Top-{} accuracy: {}%", k + 1, accuracy)?;
+        }
+        write!(f, "{}", self.calibration)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RocCurve {
+    pub fpr: Vec<f32>,
+    pub tpr: Vec<f32>,
+    // a row is predicted positive if its probability is >= threshold, the first threshold is infinite
+    pub thresholds: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrCurve {
+    pub precision: Vec<f32>,
+    pub recall: Vec<f32>,
+    pub thresholds: Vec<f32>,
+}
+
+// Reliability diagram bin of rows whose top probability is in [lower, upper)
+#[derive(Debug, Clone, Serialize)]
+pub struct CalibrationBin {
+    pub lower: f32,
+    pub upper: f32,
+    // mean top probability, NaN for empty bins
+    pub confidence: f32,
+    // fraction of correct predictions, NaN for empty bins
+    pub accuracy: f32,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Calibration {
+    pub bins: Vec<CalibrationBin>,
+    // expected calibration error, bin gaps between accuracy and confidence weighted by bin size
+    pub ece: f32,
+}
+
+impl fmt::Display for Calibration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:>12} {:>10} {:>10} {:>10}", "bin", "confidence", "accuracy", "count")?;
+        for bin in self.bins.iter() {
+            let range = format!("{:.2}-{:.2}", bin.lower, bin.upper);
+            writeln!(f, "{:>12} {:>10.4} {:>10.4} {:>10}", range, bin.confidence, bin.accuracy, bin.count)?;
+        }
+        write!(f, "ECE: {}", self.ece)
+    }
+}
+
+// One-vs-rest ROC curve of `class`
+pub fn roc_curve(probs: &Array2<f32>, labels: &Array1<usize>, class: usize) -> RocCurve {
+    let points = threshold_counts(probs, labels, class);
+    let (positives, negatives) = points.last().map_or((0, 0), |&(_, tp, fp)| (tp, fp));
+
+    let mut curve = RocCurve { fpr: vec![0.0], tpr: vec![0.0], thresholds: vec![f32::INFINITY] };
+    for (threshold, tp, fp) in points {
+        curve.fpr.push(fp as f32 / negatives as f32);
+        curve.tpr.push(tp as f32 / positives as f32);
+        curve.thresholds.push(threshold);
+    }
+    curve
+}
+
+// One-vs-rest precision-recall curve of `class`, ordered by decreasing threshold
+pub fn precision_recall_curve(probs: &Array2<f32>, labels: &Array1<usize>, class: usize) -> PrCurve {
+    let points = threshold_counts(probs, labels, class);
+    let positives = points.last().map_or(0, |&(_, tp, _)| tp);
+
+    let mut curve = PrCurve { precision: Vec::new(), recall: Vec::new(), thresholds: Vec::new() };
+    for (threshold, tp, fp) in points {
+        curve.precision.push(tp as f32 / (tp + fp) as f32);
+        curve.recall.push(tp as f32 / positives as f32);
+        curve.thresholds.push(threshold);
+    }
+    curve
+}
+
+// Area under the one-vs-rest ROC curve of every class
+pub fn roc_auc(probs: &Array2<f32>, labels: &Array1<usize>) -> Vec<f32> {
+    (0..probs.ncols())
+        .map(|class| {
+            let curve = roc_curve(probs, labels, class);
+            // trapezoidal rule
+            curve.fpr
+                .windows(2)
+                .zip(curve.tpr.windows(2))
+                .map(|(x, y)| (x[1] - x[0]) * (y[1] + y[0]) / 2.0)
+                .sum()
+        })
+        .collect()
+}
+
+// Average precision of every class, precision weighted by the recall gained at each threshold
+pub fn average_precision(probs: &Array2<f32>, labels: &Array1<usize>) -> Vec<f32> {
+    (0..probs.ncols())
+        .map(|class| {
+            let curve = precision_recall_curve(probs, labels, class);
+            let mut previous_recall = 0.0;
+            let mut ap = 0.0;
+            for (precision, recall) in curve.precision.iter().zip(curve.recall.iter()) {
+                ap += (recall - previous_recall) * precision;
+                previous_recall = *recall;
+            }
+            ap
+        })
+        .collect()
+}
+
+// Mean negative log probability of the target class, probabilities are clipped to avoid ln(0)
+pub fn log_loss(probs: &Array2<f32>, labels: &Array1<usize>) -> f32 {
+    check_shapes(probs, labels);
+    let eps = 1e-7_f32;
+    let total: f32 = probs
+        .axis_iter(Axis(0))
+        .zip(labels.iter())
+        .map(|(row, &label)| -row[label].clamp(eps, 1.0 - eps).ln())
+        .sum();
+    total / labels.len() as f32
+}
+
+// Mean squared distance between probabilities and one-hot targets, summed over classes
+pub fn brier_score(probs: &Array2<f32>, labels: &Array1<usize>) -> f32 {
+    check_shapes(probs, labels);
+    let total: f32 = probs
+        .axis_iter(Axis(0))
+        .zip(labels.iter())
+        .map(|(row, &label)| {
+            row.iter()
+                .enumerate()
+                .map(|(class, &p)| if class == label { (1.0 - p).powi(2) } else { p * p })
+                .sum::<f32>()
+        })
+        .sum();
+    total / labels.len() as f32
+}
+
+// Percentage of rows whose target is among the `k` most probable classes
+pub fn top_k_accuracy(probs: &Array2<f32>, labels: &Array1<usize>, k: usize) -> f32 {
+    check_shapes(probs, labels);
+    let correct = probs
+        .axis_iter(Axis(0))
+        .zip(labels.iter())
+        .filter(|(row, label)| {
+            // classes ranked above the target, ties count in favour of the target
+            let target = row[**label];
+            row.iter().filter(|&&p| p > target).count() < k
+        })
+        .count();
+    (correct as f32 / labels.len() as f32) * 100.0
+}
+
+// Reliability diagram with `num_bins` equal-width bins over the top probability
+pub fn calibration(probs: &Array2<f32>, labels: &Array1<usize>, num_bins: usize) -> Calibration {
+    check_shapes(probs, labels);
+    assert!(num_bins > 0, "Number of bins must be positive");
+    let mut confidence_sum = vec![0.0; num_bins];
+    let mut correct = vec![0usize; num_bins];
+    let mut count = vec![0usize; num_bins];
+
+    for (row, &label) in probs.axis_iter(Axis(0)).zip(labels.iter()) {
+        let pred = argmax(row);
+        let confidence = row[pred];
+        // a row without any number has no confidence
+        let confidence = if confidence.is_nan() { 0.0 } else { confidence };
+        let bin = ((confidence * num_bins as f32) as usize).min(num_bins - 1);
+        confidence_sum[bin] += confidence;
+        correct[bin] += (pred == label) as usize;
+        count[bin] += 1;
+    }
+
+    let total = labels.len() as f32;
+    let mut ece = 0.0;
+    let bins = (0..num_bins)
+        .map(|bin| {
+            let confidence = confidence_sum[bin] / count[bin] as f32;
+            let accuracy = correct[bin] as f32 / count[bin] as f32;
+            if count[bin] > 0 {
+                ece += count[bin] as f32 / total * (accuracy - confidence).abs();
+            }
+            CalibrationBin {
+                lower: bin as f32 / num_bins as f32,
+                upper: (bin + 1) as f32 / num_bins as f32,
+                confidence,
+                accuracy,
+                count: count[bin],
+            }
+        })
+        .collect();
+
+    Calibration { bins, ece }
+}
+
+// (threshold, true positives, false positives) at every distinct probability of `class`, highest first
+fn threshold_counts(probs: &Array2<f32>, labels: &Array1<usize>, class: usize) -> Vec<(f32, usize, usize)> {
+    check_shapes(probs, labels);
+    let mut scored: Vec<(f32, bool)> = probs
+        .column(class)
+        .iter()
+        .zip(labels.iter())
+        .map(|(&p, &label)| (p, label == class))
+        .collect();
+    scored.sort_by(|a, b| cmp_probability(b.0, a.0));
+
+    let mut points: Vec<(f32, usize, usize)> = Vec::new();
+    let (mut tp, mut fp) = (0, 0);
+    for (i, &(p, positive)) in scored.iter().enumerate() {
+        if positive { tp += 1 } else { fp += 1 }
+        // rows with equal probability pass the threshold together
+        if i + 1 == scored.len() || scored[i + 1].0 != p {
+            points.push((p, tp, fp));
+        }
+    }
+    points
+}
+
+// None if no value is defined
+fn nan_mean(values: &[f32]) -> Option<f32> {
+    let defined: Vec<f32> = values.iter().copied().filter(|v| !v.is_nan()).collect();
+    (!defined.is_empty()).then(|| defined.iter().sum::<f32>() / defined.len() as f32)
+}
+
+// Total order of probabilities with NaN below every number, so a NaN never wins the argmax
+// and is the last to pass a threshold
+fn cmp_probability<F: Float>(a: F, b: F) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => a.partial_cmp(&b).unwrap(),
+    }
+}
+
+// Index of the largest value of a row of logits or probabilities, the last one on ties.
+// NaN ranks lowest, so a diverged model still predicts a class instead of panicking.
+pub(crate) fn argmax<F: Float>(row: ArrayView1<F>) -> usize {
+    row.iter()
+        .enumerate()
+        .max_by(|a, b| cmp_probability(*a.1, *b.1))
+        .expect("Rows must have at least one class")
+        .0
+}
+
+fn check_shapes(probs: &Array2<f32>, labels: &Array1<usize>) {
+    assert_eq!(probs.nrows(), labels.len(), "Probabilities and labels must have the same number of rows");
+    assert!(labels.iter().all(|&l| l < probs.ncols()), "Labels must be smaller than the number of classes");
+}
@@ -1,37 +1,39 @@
 use std::error::Error;
 use std::fs::File;
 
+use std::mem::size_of;
+
 use ndarray::{Array1, Array2};
 use polars::prelude::*;
 
-pub fn array2_to_data_frame(array: &Array2<f32>, name: &str, idx: usize) -> DataFrame {
+use super::Float;
+
+pub fn array2_to_data_frame<F: Float>(array: &Array2<F>, name: &str, idx: usize) -> DataFrame {
     let cols = array.shape()[1];
 
     let mut columns: Vec<Column> = Vec::new();
 
     for i in 0..cols {
-        let col: Vec<f32> = array.column(i).to_vec();
         let col_name = format!("{}{}_{}", name, idx, i);
-        columns.push(Column::new(PlSmallStr::from(col_name), col));
+        columns.push(column(&col_name, array.column(i).iter()));
     }
 
     DataFrame::new(columns).expect("Failed to create weights DataFrame")
 }
 
-pub fn array1_to_data_frame(array: &Array1<f32>, name: &str, idx: usize) -> DataFrame {
+pub fn array1_to_data_frame<F: Float>(array: &Array1<F>, name: &str, idx: usize) -> DataFrame {
     let col_name = format!("{}{}", name, idx);
-    let mut bias: Vec<Column> = Vec::new();
-    bias.push(Column::new(PlSmallStr::from(col_name), array.to_vec()));
+    let bias: Vec<Column> = vec![column(&col_name, array.iter())];
     DataFrame::new(bias).expect("Failed to create bias DataFrame")
 }
 
-pub fn save_model (all_weights: Vec<&mut Array2<f32>>, all_bias: Vec<&mut Array1<f32>>, save_path: &str) -> Result<(), Box<dyn Error>> {
+pub fn save_model<F: Float>(all_weights: Vec<&mut Array2<F>>, all_bias: Vec<&mut Array1<F>>, save_path: &str) -> Result<(), Box<dyn Error>> {
     
     for (idx, weight) in all_weights.into_iter().enumerate() {
         let full_save_path = format!("{}/w{}.csv", save_path, idx);
         let mut file = File::create(full_save_path).expect("could not weight create file");
         
-        let mut wdf = array2_to_data_frame(&weight, "w", idx);
+        let mut wdf = array2_to_data_frame(weight, "w", idx);
 
         CsvWriter::new(&mut file)
         .include_header(true)
@@ -44,7 +46,7 @@ pub fn save_model (all_weights: Vec<&mut Array2<f32>>, all_bias: Vec<&mut Array1
         let full_save_path = format!("{}/b{}.csv", save_path, idx);
         let mut file = File::create(full_save_path).expect("could not bias create file");
         
-        let mut bdf = array1_to_data_frame(&bias, "b", idx);
+        let mut bdf = array1_to_data_frame(bias, "b", idx);
 
         CsvWriter::new(&mut file)
         .include_header(true)
@@ -54,4 +56,14 @@ pub fn save_model (all_weights: Vec<&mut Array2<f32>>, all_bias: Vec<&mut Array1
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+// f64 columns for f64 models so no precision is lost, f32 otherwise (f16 and bf16 fit exactly)
+fn column<'a, F: Float>(name: &str, values: impl Iterator<Item = &'a F>) -> Column {
+    let name = PlSmallStr::from(name);
+    if size_of::<F>() > size_of::<f32>() {
+        Column::new(name, values.map(|v| v.into_f64()).collect::<Vec<f64>>())
+    } else {
+        Column::new(name, values.map(|v| v.into_f32()).collect::<Vec<f32>>())
+    }
+}
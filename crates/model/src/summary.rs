@@ -0,0 +1,128 @@
+use std::fmt;
+use std::mem::size_of;
+
+use super::{NN, Float, LayerTypes, Loss, OptimizerState, Propagate, Regularization};
+
+pub struct LayerSummary {
+    pub name: &'static str,
+    // output width, None if no `Layer` or `AutogradLayer` fixes the input width
+    pub output_dim: Option<usize>,
+    // weights + bias
+    pub params: usize,
+    pub trainable: bool,
+    pub regularization: Option<Regularization>,
+}
+
+pub struct ModelSummary {
+    pub layers: Vec<LayerSummary>,
+    pub batch_size: usize,
+    // element type, e.g. "f32"
+    pub dtype: &'static str,
+    pub total_params: usize,
+    pub trainable_params: usize,
+    // estimated bytes at `batch_size`
+    pub param_bytes: usize,
+    pub grad_bytes: usize,
+    pub optimizer_bytes: usize,
+    pub activation_bytes: usize,
+}
+
+impl ModelSummary {
+    pub fn total_bytes(&self) -> usize {
+        self.param_bytes + self.grad_bytes + self.optimizer_bytes + self.activation_bytes
+    }
+}
+
+impl<F: Float, S: Loss<F>, O: OptimizerState> NN<S, O, F> {
+    // Layers with output width and parameter counts, and the memory needed to train at `batch_size`
+    pub fn summary(&self, batch_size: usize) -> ModelSummary {
+        let mut width = self.layers.iter().find_map(|l| match l {
+            LayerTypes::Layer(layer) => Some(layer.input_dim()),
+            LayerTypes::Autograd(layer) => Some(layer.input_dim()),
+            _ => None,
+        });
+
+        let mut layers = Vec::new();
+        let mut activation_values = 0;
+        for layer in self.layers.iter() {
+            let (params, regularization) = match layer {
+                LayerTypes::Layer(layer) => {
+                    // dense layers cache their input, activations their input or output of the same width
+                    activation_values += batch_size * layer.input_dim();
+                    width = Some(layer.output_dim());
+                    ((layer.input_dim() + 1) * layer.output_dim(), Some(*layer.regularization()))
+                }
+                LayerTypes::Autograd(layer) => {
+                    // only the input is cached, the tape is rebuilt in backward
+                    activation_values += batch_size * layer.input_dim();
+                    width = Some(layer.output_dim());
+                    (layer.num_params(), Some(*layer.regularization()))
+                }
+                _ => {
+                    activation_values += batch_size * width.unwrap_or(0);
+                    (0, None)
+                }
+            };
+            layers.push(LayerSummary {
+                name: layer.name(),
+                output_dim: width,
+                params,
+                trainable: params > 0 && !layer.is_frozen(),
+                regularization,
+            });
+        }
+
+        let total_params: usize = layers.iter().map(|l| l.params).sum();
+        let trainable_params: usize = layers.iter().filter(|l| l.trainable).map(|l| l.params).sum();
+        let bytes = size_of::<F>();
+        let state_params = if self.optim.keeps_frozen_state() { total_params } else { trainable_params };
+        ModelSummary {
+            layers,
+            batch_size,
+            dtype: F::NAME,
+            total_params,
+            trainable_params,
+            param_bytes: total_params * bytes,
+            // every trainable layer keeps gradient buffers, frozen or not
+            grad_bytes: total_params * bytes,
+            optimizer_bytes: state_params * self.optim.state_per_param() * bytes,
+            activation_bytes: activation_values * bytes,
+        }
+    }
+}
+
+impl fmt::Display for ModelSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<4} {:<10} {:>14} {:>10}  Regularization", "#", "Layer", "Output Shape", "Param #")?;
+        for (idx, layer) in self.layers.iter().enumerate() {
+            let shape = match layer.output_dim {
+                Some(dim) => format!("({}, {})", self.batch_size, dim),
+                None => format!("({}, ?)", self.batch_size),
+            };
+            let frozen = if layer.params > 0 && !layer.trainable { " (frozen)" } else { "" };
+            let regularization = layer.regularization.map_or(String::from("-"), |r| format!("{:?}", r));
+            writeln!(f, "{:<4} {:<10} {:>14} {:>10}  {}{}", idx, layer.name, shape, layer.params, regularization, frozen)?;
+        }
+        writeln!(f, "Total params: {}", self.total_params)?;
+        writeln!(f, "Trainable params: {}", self.trainable_params)?;
+        writeln!(f, "Non-trainable params: {}", self.total_params - self.trainable_params)?;
+        writeln!(f, "Estimated memory at batch size {} in {}:", self.batch_size, self.dtype)?;
+        writeln!(f, "  parameters: {}", format_bytes(self.param_bytes))?;
+        writeln!(f, "  gradients: {}", format_bytes(self.grad_bytes))?;
+        writeln!(f, "  optimizer state: {}", format_bytes(self.optimizer_bytes))?;
+        writeln!(f, "  activation caches: {}", format_bytes(self.activation_bytes))?;
+        write!(f, "  total: {}", format_bytes(self.total_bytes()))
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    let kb = 1024.0;
+    let bytes_f = bytes as f64;
+    if bytes_f < kb {
+        format!("{} B", bytes)
+    } else if bytes_f < kb * kb {
+        format!("{:.2} KiB", bytes_f / kb)
+    } else {
+        format!("{:.2} MiB", bytes_f / (kb * kb))
+    }
+}
@@ -0,0 +1,90 @@
+use std::error::Error;
+use std::mem;
+
+use ndarray::{Array1, Array2};
+
+use super::{NN, Float, Loss, Propagate, save_model};
+
+pub enum Averaging {
+    // shadow = decay * shadow + (1 - decay) * weights
+    EMA { decay: f32 },
+    // running mean of the weights of every step from `start` onwards
+    SWA { start: usize },
+}
+
+pub struct WeightAverage<F = f32> {
+    pub mode: Averaging,
+    pub shadow_w: Vec<Array2<F>>,
+    pub shadow_b: Vec<Array1<F>>,
+    pub num_steps: usize,
+    pub num_averaged: usize,
+}
+
+impl<F: Float> WeightAverage<F> {
+    pub fn new(mode: Averaging) -> Self {
+        WeightAverage {
+            mode,
+            shadow_w: Vec::new(),
+            shadow_b: Vec::new(),
+            num_steps: 0,
+            num_averaged: 0,
+        }
+    }
+
+    // Call after every `train_step` to fold the current weights into the shadow copies
+    pub fn update<S: Loss<F>, O>(&mut self, nn: &mut NN<S, O, F>) {
+        let params: Vec<_> = nn.layers
+            .iter_mut()
+            .filter_map(|l| l.params_grads())
+            .map(|(weights, _, bias, _)| (weights, bias))
+            .collect();
+
+        // first call or layers changed, start from the current weights
+        if self.shadow_w.len() != params.len() {
+            self.shadow_w = params.iter().map(|(w, _)| (**w).clone()).collect();
+            self.shadow_b = params.iter().map(|(_, b)| (**b).clone()).collect();
+            self.num_averaged = 1;
+            self.num_steps += 1;
+            return;
+        }
+
+        let factor = F::from_f32(match self.mode {
+            Averaging::EMA { decay } => 1.0 - decay,
+            Averaging::SWA { start } => {
+                if self.num_steps <= start {
+                    // not averaging yet, follow the live weights until step `start` starts the mean
+                    self.num_averaged = 0;
+                    1.0
+                } else {
+                    1.0 / (self.num_averaged + 1) as f32
+                }
+            }
+        });
+
+        for ((w, b), (sw, sb)) in params.into_iter()
+            .zip(self.shadow_w.iter_mut().zip(self.shadow_b.iter_mut())) {
+            sw.zip_mut_with(w, |s, &x| *s += factor * (x - *s));
+            sb.zip_mut_with(b, |s, &x| *s += factor * (x - *s));
+        }
+
+        self.num_averaged += 1;
+        self.num_steps += 1;
+    }
+
+    // Exchange shadow weights with the live weights of `nn`.
+    // Call once before evaluation or saving and once more to restore the training weights.
+    pub fn swap<S: Loss<F>, O>(&mut self, nn: &mut NN<S, O, F>) {
+        let params = nn.layers.iter_mut().filter_map(|l| l.params_grads());
+
+        for ((w, _, b, _), (sw, sb)) in params
+            .zip(self.shadow_w.iter_mut().zip(self.shadow_b.iter_mut())) {
+            mem::swap(w, sw);
+            mem::swap(b, sb);
+        }
+    }
+
+    // Save the averaged weights in the same layout as `save_model`
+    pub fn save(&mut self, save_path: &str) -> Result<(), Box<dyn Error>> {
+        save_model(self.shadow_w.iter_mut().collect(), self.shadow_b.iter_mut().collect(), save_path)
+    }
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::*;
+use model::*;
+use ndarray::s;
+
+fn model(accumulate: bool) -> NN<CrossEntropyLoss, MomentumOptimizer> {
+    let mut nn = mlp(8, 4, 5, 3, MomentumOptimizer::with_lr(0.1));
+    nn.set_accumulate_grads(accumulate);
+    nn
+}
+
+fn assert_close(a: &mut NN<CrossEntropyLoss, MomentumOptimizer>, b: &mut NN<CrossEntropyLoss, MomentumOptimizer>) {
+    let difference = flatten_params(&mut a.layers) - flatten_params(&mut b.layers);
+    assert!(difference.iter().all(|d| d.abs() < 1e-6), "{}", difference);
+}
+
+#[test]
+fn micro_batches_match_the_full_batch() {
+    let (x, y) = (inputs(8, 4), labels(8, 3));
+    let (mut full, mut micro) = (model(false), model(true));
+    for step in 0..3 {
+        full.train_step(&x, &y, step, usize::MAX, "");
+        for half in [s![..4, ..], s![4.., ..]] {
+            micro.accumulate_step(&x.slice(half).to_owned(), &y.slice(half).to_owned());
+        }
+        micro.step_accumulated(2);
+    }
+    assert_close(&mut full, &mut micro);
+}
+
+#[test]
+fn training_steps_ignore_accumulate_mode() {
+    let (x, y) = (inputs(8, 4), labels(8, 3));
+    let (mut plain, mut accumulating) = (model(false), model(true));
+    // leftover gradients from a micro-batch that was never stepped
+    accumulating.accumulate_step(&x, &y);
+    for step in 0..3 {
+        plain.train_step(&x, &y, step, usize::MAX, "");
+        accumulating.train_step(&x, &y, step, usize::MAX, "");
+        plain.train_step_sam(&x, &y, 0.05);
+        accumulating.train_step_sam(&x, &y, 0.05);
+        plain.train_step_parallel(&x, &y, 2).unwrap();
+        accumulating.train_step_parallel(&x, &y, 2).unwrap();
+    }
+    assert_close(&mut plain, &mut accumulating);
+}
+
+#[test]
+fn closure_step_ignores_accumulate_mode() {
+    let (x, y) = (inputs(8, 4), labels(8, 3));
+    let build = |accumulate: bool| {
+        let mut nn = mlp::<f32, _>(8, 4, 5, 3, LBFGSOptimizer::default());
+        nn.set_accumulate_grads(accumulate);
+        nn
+    };
+    let (mut plain, mut accumulating) = (build(false), build(true));
+    let (plain_loss, _) = plain.train_step_closure(&x, &y);
+    let (accumulating_loss, _) = accumulating.train_step_closure(&x, &y);
+    assert!((plain_loss - accumulating_loss).abs() < 1e-6, "{} vs {}", plain_loss, accumulating_loss);
+}
+
+#[test]
+#[should_panic(expected = "at least one micro-batch")]
+fn step_accumulated_needs_micro_batches() {
+    model(true).step_accumulated(0);
+}
+
+#[test]
+fn accumulate_step_turns_accumulation_on() {
+    let (x, y) = (inputs(8, 4), labels(8, 3));
+    let (mut full, mut micro) = (model(false), model(false));
+    for step in 0..3 {
+        full.train_step(&x, &y, step, usize::MAX, "");
+        // gradients of the earlier steps must not leak into the first micro-batch
+        micro.train_step(&x, &y, step, usize::MAX, "");
+    }
+    full.train_step(&x, &y, 3, usize::MAX, "");
+    for half in [s![..4, ..], s![4.., ..]] {
+        micro.accumulate_step(&x.slice(half).to_owned(), &y.slice(half).to_owned());
+    }
+    assert!(micro.is_accumulating());
+    micro.step_accumulated(2);
+    assert_close(&mut full, &mut micro);
+}
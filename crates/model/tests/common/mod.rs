@@ -0,0 +1,28 @@
+// Fixtures shared by the integration tests, not every test file uses all of them
+#![allow(dead_code)]
+
+use model::*;
+use ndarray::Array2;
+
+// deterministic inputs away from the kinks of ReLU-like activations at 0
+pub fn inputs(rows: usize, cols: usize) -> Array2<f32> {
+    Array2::from_shape_fn((rows, cols), |(i, j)| {
+        let x = ((i * cols + j) as f32 * 2.399 + 0.3).sin() * 2.0;
+        if x.abs() < 0.1 { x + 0.2 } else { x }
+    })
+}
+
+pub fn labels(rows: usize, classes: usize) -> Array2<f32> {
+    Array2::from_shape_fn((rows, 1), |(i, _)| (i % classes) as f32)
+}
+
+// dense -> ELU -> dense classifier with weights drawn from `seed`
+pub fn mlp<F: Float, O>(seed: u64, input_dim: usize, hidden: usize, classes: usize, optim: O) -> NN<CrossEntropyLoss<F>, O, F> {
+    set_seed(seed);
+    NNBuilder::<F>::new(input_dim)
+        .dense(hidden, Initialization::He, Regularization::L2 { lambda: 0.001 })
+        .layer(LayerTypes::ELU(ELU::new(1.0)))
+        .dense(classes, Initialization::Glorot, Regularization::None)
+        .build(CrossEntropyLoss::new(), optim)
+        .unwrap()
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::*;
+use model::*;
+use ndarray::{Array1, Array2, Axis};
+
+// the first dense layer carries the constraint
+fn model<O>(constraint: Constraint, optim: O) -> NN<CrossEntropyLoss, O> {
+    set_seed(12);
+    let constrained = Layer::new(4, 6, Initialization::Glorot, Regularization::None).with_constraint(constraint);
+    NNBuilder::new(4)
+        .layer(LayerTypes::Layer(constrained))
+        .layer(LayerTypes::ELU(ELU::new(1.0)))
+        .dense(3, Initialization::Glorot, Regularization::None)
+        .build(CrossEntropyLoss::new(), optim)
+        .unwrap()
+}
+
+fn weights<O>(nn: &mut NN<CrossEntropyLoss, O>) -> Array2<f32> {
+    nn.layers[0].params_grads().unwrap().0.clone()
+}
+
+fn column_norms(w: &Array2<f32>) -> Array1<f32> {
+    w.map_axis(Axis(0), |column| column.dot(&column).sqrt())
+}
+
+#[test]
+fn max_norm_clamps_only_long_columns() {
+    let (x, y) = (inputs(16, 4), labels(16, 3));
+    let mut free = model(Constraint::None, SGDOptimizer::new(5.0));
+    let mut clamped = model(Constraint::MaxNorm { max: 1.0 }, SGDOptimizer::new(5.0));
+    free.train_step(&x, &y, 0, usize::MAX, "");
+    clamped.train_step(&x, &y, 0, usize::MAX, "");
+
+    let (free, clamped) = (weights(&mut free), weights(&mut clamped));
+    let norms = column_norms(&free);
+    assert!(norms.iter().any(|&n| n > 1.0) && norms.iter().any(|&n| n < 1.0), "{}", norms);
+    for (j, &norm) in norms.iter().enumerate() {
+        if norm > 1.0 {
+            // same direction at norm 1
+            let expected = free.column(j).mapv(|w| w / norm);
+            assert!(clamped.column(j).iter().zip(expected.iter()).all(|(a, b)| (a - b).abs() < 1e-5));
+        } else {
+            assert_eq!(clamped.column(j), free.column(j));
+        }
+    }
+}
+
+#[test]
+fn unit_norm_after_every_kind_of_step() {
+    let (x, y) = (inputs(16, 4), labels(16, 3));
+    let unit = |w: Array2<f32>| column_norms(&w).iter().all(|n| (n - 1.0).abs() < 1e-5);
+
+    let mut nn = model(Constraint::UnitNorm, AdamOptimizer::with_lr(0.1));
+    nn.train_step(&x, &y, 0, usize::MAX, "");
+    assert!(unit(weights(&mut nn)));
+    nn.train_step_parallel(&x, &y, 3).unwrap();
+    assert!(unit(weights(&mut nn)));
+
+    let mut nn = model(Constraint::UnitNorm, LBFGSOptimizer::default());
+    nn.train_step_closure(&x, &y);
+    assert!(unit(weights(&mut nn)));
+}
+
+#[test]
+fn frozen_layers_keep_their_norms() {
+    let (x, y) = (inputs(16, 4), labels(16, 3));
+    let mut nn = model(Constraint::UnitNorm, SGDOptimizer::new(5.0));
+    let before = weights(&mut nn);
+    nn.freeze(0);
+    nn.train_step(&x, &y, 0, usize::MAX, "");
+    assert_eq!(weights(&mut nn), before);
+}
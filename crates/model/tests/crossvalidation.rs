@@ -0,0 +1,65 @@
+use model::*;
+use ndarray::{array, Array2};
+
+// zero weights predict 0.5 for both classes, ties go to the last class
+fn untrained() -> NN<CrossEntropyLoss, LBFGSOptimizer> {
+    NNBuilder::new(2)
+        .dense(2, Initialization::Constant(0.0), Regularization::None)
+        .build(CrossEntropyLoss::new(), LBFGSOptimizer::default())
+        .unwrap()
+}
+
+fn folds() -> Vec<(Vec<usize>, Vec<usize>)> {
+    vec![(vec![3, 4, 5], vec![0, 1, 2]), (vec![0, 1, 2], vec![3, 4, 5])]
+}
+
+#[test]
+fn scores_every_fold_with_a_fresh_model() {
+    let x = Array2::from_shape_fn((6, 2), |(i, j)| (i + j) as f32);
+    let y = array![[1.0], [1.0], [0.0], [1.0], [0.0], [0.0]];
+    let mut trained_rows = Vec::new();
+    let cv = cross_validate(
+        &x,
+        &y,
+        &folds(),
+        untrained,
+        |_, x, _| trained_rows.push(x.column(0).to_vec()),
+        &[("accuracy", accuracy::<f32>)],
+    );
+
+    assert_eq!(trained_rows, vec![vec![3.0, 4.0, 5.0], vec![0.0, 1.0, 2.0]]);
+    assert_eq!(cv.metric_names, vec!["accuracy"]);
+    assert!((cv.fold_scores[[0, 0]] - 200.0 / 3.0).abs() < 1e-4);
+    assert!((cv.fold_scores[[1, 0]] - 100.0 / 3.0).abs() < 1e-4);
+    assert!((cv.mean().unwrap()[0] - 50.0).abs() < 1e-4);
+    assert!((cv.std().unwrap()[0] - 50.0 / 3.0).abs() < 1e-4);
+    assert!(cv.to_string().contains("Fold 1: accuracy = 33.33"));
+}
+
+#[test]
+fn closure_optimizers_train_per_fold() {
+    let x = Array2::from_shape_fn((6, 2), |(i, j)| ((i * 2 + j) as f32).sin());
+    let y = array![[1.0], [0.0], [1.0], [0.0], [1.0], [0.0]];
+    let cv = cross_validate(
+        &x,
+        &y,
+        &folds(),
+        untrained,
+        |nn, x, y| {
+            nn.train_step_closure(x, y);
+        },
+        &[("accuracy", accuracy::<f32>)],
+    );
+    assert_eq!(cv.fold_scores.dim(), (2, 1));
+    assert!(cv.fold_scores.iter().all(|s| s.is_finite()));
+}
+
+#[test]
+fn no_folds_has_no_mean() {
+    let (x, y) = (Array2::zeros((2, 2)), Array2::zeros((2, 1)));
+    let cv = cross_validate(&x, &y, &[], untrained, |_, _, _| {}, &[("accuracy", accuracy::<f32>)]);
+    assert_eq!(cv.fold_scores.dim(), (0, 1));
+    assert_eq!(cv.mean(), None);
+    assert_eq!(cv.std(), None);
+    assert_eq!(cv.to_string(), "No folds");
+}
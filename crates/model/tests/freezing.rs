@@ -0,0 +1,102 @@
+mod common;
+
+use common::*;
+use model::*;
+use ndarray::Array2;
+
+// dense layers at 0, 2 and 4 with different shapes
+fn model<O: Optimizer>(optim: O) -> NN<CrossEntropyLoss, O> {
+    set_seed(4);
+    NNBuilder::new(5)
+        .dense(4, Initialization::He, Regularization::None)
+        .layer(LayerTypes::ReLu(ReLu::new()))
+        .dense(3, Initialization::He, Regularization::None)
+        .layer(LayerTypes::ReLu(ReLu::new()))
+        .dense(2, Initialization::Glorot, Regularization::None)
+        .build(CrossEntropyLoss::new(), optim)
+        .unwrap()
+}
+
+fn weights<O: Optimizer>(nn: &mut NN<CrossEntropyLoss, O>, idx: usize) -> Array2<f32> {
+    nn.layers[idx].params_grads().unwrap().0.clone()
+}
+
+fn train<O: Optimizer>(nn: &mut NN<CrossEntropyLoss, O>, steps: usize) {
+    let (x, y) = (inputs(8, 5), labels(8, 2));
+    for step in 0..steps {
+        nn.train_step(&x, &y, step, usize::MAX, "");
+    }
+}
+
+// freeze(0), train, unfreeze(0) and freeze(2), train again
+fn assert_changing_frozen_set<O: Optimizer>(name: &str, optim: O) {
+    let mut nn = model(optim);
+    nn.freeze(0);
+    let first = weights(&mut nn, 0);
+    train(&mut nn, 4);
+    assert_eq!(weights(&mut nn, 0), first, "{}: frozen layer 0 changed", name);
+
+    nn.unfreeze(0);
+    nn.freeze(2);
+    let second = weights(&mut nn, 2);
+    train(&mut nn, 4);
+    assert_eq!(weights(&mut nn, 2), second, "{}: frozen layer 2 changed", name);
+    assert_ne!(weights(&mut nn, 0), first, "{}: unfrozen layer 0 did not train", name);
+}
+
+#[test]
+fn sgd_changing_frozen_set() {
+    assert_changing_frozen_set("SGD", SGDOptimizer::new(0.1));
+}
+
+#[test]
+fn momentum_changing_frozen_set() {
+    assert_changing_frozen_set("Momentum", MomentumOptimizer::with_lr(0.1));
+}
+
+#[test]
+fn nag_changing_frozen_set() {
+    assert_changing_frozen_set("NAG", NAGOptimizer::with_lr(0.1));
+}
+
+#[test]
+fn rmsprop_changing_frozen_set() {
+    assert_changing_frozen_set("RMSProp", RMSPropOptimizer::with_lr(0.01));
+}
+
+#[test]
+fn adam_changing_frozen_set() {
+    assert_changing_frozen_set("Adam", AdamOptimizer::with_lr(0.01));
+}
+
+#[test]
+fn nadam_changing_frozen_set() {
+    assert_changing_frozen_set("Nadam", NadamOptimizer::with_lr(0.01));
+}
+
+#[test]
+fn lookahead_changing_frozen_set() {
+    // syncs every 2 steps, a sync must not move frozen layers either
+    assert_changing_frozen_set("Lookahead", Lookahead::new(AdamOptimizer::with_lr(0.01), 2, 0.5));
+}
+
+#[test]
+fn optimizer_state_stays_with_its_layer() {
+    // frozen layer 0 keeps its slot, with untouched moments
+    let mut nn = model(AdamOptimizer::with_lr(0.01));
+    nn.freeze(0);
+    train(&mut nn, 1);
+    assert_eq!(nn.optim.velocity_w.len(), 3);
+    assert_eq!(nn.optim.velocity_w[0], Some(Array2::zeros((5, 4))));
+    assert_eq!(nn.optim.velocity_w[2].as_ref().unwrap().dim(), (3, 2));
+}
+
+#[test]
+fn summary_keeps_optimizer_state_of_frozen_layers() {
+    let mut nn = model(AdamOptimizer::default());
+    nn.freeze(0);
+    let summary = nn.summary(8);
+    assert_eq!((summary.total_params, summary.trainable_params), (47, 23));
+    // moments stay allocated for the frozen layer so their slots keep their position
+    assert_eq!(summary.optimizer_bytes, 47 * 2 * 4);
+}
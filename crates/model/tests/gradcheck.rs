@@ -0,0 +1,188 @@
+mod common;
+
+use common::*;
+use model::*;
+use std::sync::Arc;
+
+use ndarray::{Array1, Axis};
+
+const EPS: f32 = 1e-2;
+const TOLERANCE: f32 = 1e-2;
+
+fn assert_layer<P: Propagate>(name: &str, mut layer: P) {
+    let check = gradcheck(&mut layer, &inputs(4, 5), EPS);
+    assert!(check.max_error() < TOLERANCE, "{}: {:?}", name, check);
+}
+
+fn dense(regularization: Regularization) -> Layer {
+    set_seed(0);
+    Layer::new(5, 3, Initialization::Glorot, regularization).with_bias(Initialization::Glorot)
+}
+
+#[test]
+fn dense_layer() {
+    assert_layer("Layer", dense(Regularization::None));
+}
+
+#[test]
+fn dense_layer_l1() {
+    assert_layer("Layer L1", dense(Regularization::L1 { lambda: 0.1 }));
+}
+
+#[test]
+fn dense_layer_l2() {
+    assert_layer("Layer L2", dense(Regularization::L2 { lambda: 0.1 }));
+}
+
+#[test]
+fn dense_layer_elastic_net() {
+    assert_layer("Layer ElasticNet", dense(Regularization::ElasticNet { l1: 0.05, l2: 0.1 }));
+}
+
+#[test]
+fn relu() {
+    assert_layer("ReLu", ReLu::new());
+}
+
+#[test]
+fn leaky_relu() {
+    assert_layer("LeakyReLu", LeakyReLu::new(0.1));
+}
+
+#[test]
+fn elu() {
+    assert_layer("ELU", ELU::new(1.0));
+}
+
+#[test]
+fn selu() {
+    assert_layer("SELU", SELU::new(1.6733, 1.0507));
+}
+
+#[test]
+fn layer_types() {
+    assert_layer("LayerTypes::ELU", LayerTypes::ELU(ELU::new(0.5)));
+    assert_layer("LayerTypes::Layer", LayerTypes::Layer(dense(Regularization::L2 { lambda: 0.01 })));
+}
+
+fn autograd_layer(input_dim: usize, weights_dim: (usize, usize), block: BlockFn) -> AutogradLayer {
+    set_seed(3);
+    let weights = with_rng(|rng| Initialization::Glorot.sample(weights_dim, weights_dim.0, weights_dim.1, rng));
+    let bias = Array1::from_shape_fn(weights_dim.1, |j| 0.1 * j as f32 - 0.2);
+    AutogradLayer::new(input_dim, weights, bias, block)
+}
+
+#[test]
+fn autograd_dense_tanh() {
+    let layer = autograd_layer(5, (5, 3), Arc::new(|x, w, b| (x.matmul(w) + b).tanh()));
+    assert_layer("AutogradLayer tanh", layer.with_regularization(Regularization::L2 { lambda: 0.1 }));
+}
+
+#[test]
+fn autograd_ops() {
+    let layer = autograd_layer(5, (5, 3), Arc::new(|x, w, b| {
+        let scores = (x.matmul(w) + b).log_softmax();
+        let weighted = (x.exp() / x.exp().sum_axis(Axis(1))).matmul(w.t().t()).sigmoid();
+        scores * weighted - (x.relu().powi(2).add_scalar(1.0).ln().sum_axis(Axis(1)) - b.softmax()).scale(0.5)
+    }));
+    assert_layer("AutogradLayer ops", layer);
+}
+
+// inputs are [x (3) | previous cell state (2)], outputs [h | c], gates packed as i, f, g, o in the weights
+#[test]
+fn autograd_lstm_cell() {
+    let layer = autograd_layer(5, (3, 8), Arc::new(|x, w, b| {
+        let (input, cell) = (x.slice_cols(0, 3), x.slice_cols(3, 5));
+        let z = input.matmul(w) + b;
+        let (i, f) = (z.slice_cols(0, 2).sigmoid(), z.slice_cols(2, 4).sigmoid());
+        let (g, o) = (z.slice_cols(4, 6).tanh(), z.slice_cols(6, 8).sigmoid());
+        let cell = f * cell + i * g;
+        Var::concat_cols(&[o * cell.tanh(), cell])
+    }));
+    assert_eq!(layer.output_dim(), 4);
+    assert_layer("AutogradLayer LSTM cell", layer);
+}
+
+#[test]
+fn autograd_layer_matches_dense() {
+    let mut autograd = autograd_layer(5, (5, 3), Arc::new(|x, w, b| x.matmul(w) + b));
+    let (weights, _, bias, _) = autograd.params_grads().unwrap();
+    let mut layer = Layer::new(5, 3, Initialization::Zeros, Regularization::None);
+    layer.set_params(weights.clone(), bias.clone());
+
+    let x = inputs(4, 5);
+    let grad = inputs(4, 3);
+    assert_eq!(autograd.forward(&x), layer.forward(&x));
+    let diff = (autograd.backward(&grad) - layer.backward(&grad)).mapv(f32::abs).sum();
+    assert!(diff < 1e-5, "{}", diff);
+}
+
+#[test]
+fn autograd_loss() {
+    let targets = inputs(6, 4) * 0.5;
+    let mut loss = AutogradLoss::new(Arc::new(|preds, targets| (preds - targets).powi(2).mean()));
+    let error = gradcheck_loss(&mut loss, &inputs(6, 4), &targets, EPS);
+    assert!(error < TOLERANCE, "AutogradLoss: {}", error);
+}
+
+#[test]
+fn cross_entropy_loss() {
+    let error = gradcheck_loss(&mut CrossEntropyLoss::new(), &inputs(6, 4), &labels(6, 4), EPS);
+    assert!(error < TOLERANCE, "CrossEntropyLoss: {}", error);
+}
+
+#[test]
+fn mse_loss() {
+    let error = gradcheck_loss(&mut MSELoss::new(), &inputs(6, 4), &labels(6, 4), EPS);
+    assert!(error < TOLERANCE, "MSELoss: {}", error);
+}
+
+#[test]
+fn whole_model() {
+    // weights and pre-activations of this seed stay further than EPS from the L1 and LeakyReLU kinks
+    set_seed(2);
+    let mut nn = NNBuilder::new(5)
+        .dense(6, Initialization::He, Regularization::L2 { lambda: 0.01 })
+        .layer(LayerTypes::ELU(ELU::new(1.0)))
+        .dense(4, Initialization::Glorot, Regularization::ElasticNet { l1: 0.01, l2: 0.01 })
+        .layer(LayerTypes::LeakyReLu(LeakyReLu::new(0.1)))
+        .dense(3, Initialization::LeCun, Regularization::None)
+        .build(CrossEntropyLoss::new(), SGDOptimizer::default())
+        .unwrap();
+    let check = gradcheck_model(&mut nn, &inputs(4, 5), &labels(4, 3), EPS);
+    assert!(check.max_error() < TOLERANCE, "{:?}", check);
+}
+
+#[test]
+fn frozen_layers_are_skipped() {
+    set_seed(2);
+    let mut nn = NNBuilder::new(5)
+        .dense(4, Initialization::He, Regularization::None)
+        .layer(LayerTypes::SELU(SELU::new(1.6733, 1.0507)))
+        .dense(3, Initialization::He, Regularization::None)
+        .build(MSELoss::new(), SGDOptimizer::default())
+        .unwrap();
+    nn.freeze(0);
+    let check = gradcheck_model(&mut nn, &inputs(4, 5), &labels(4, 3), EPS);
+    assert!(check.max_error() < TOLERANCE, "{:?}", check);
+}
+
+#[test]
+fn whole_model_with_autograd_layer() {
+    let mut nn = NNBuilder::new(5)
+        .layer(LayerTypes::Autograd(autograd_layer(5, (5, 4), Arc::new(|x, w, b| (x.matmul(w) + b).tanh()))))
+        .dense(3, Initialization::Glorot, Regularization::L2 { lambda: 0.01 })
+        .build(CrossEntropyLoss::new(), SGDOptimizer::default())
+        .unwrap();
+    let check = gradcheck_model(&mut nn, &inputs(4, 5), &labels(4, 3), EPS);
+    assert!(check.max_error() < TOLERANCE, "{:?}", check);
+    assert_eq!(nn.summary(4).total_params, 5 * 4 + 4 + 4 * 3 + 3);
+}
+
+#[test]
+fn builder_checks_autograd_width() {
+    let result = NNBuilder::new(6)
+        .layer(LayerTypes::Autograd(autograd_layer(5, (5, 4), Arc::new(|x, w, b| x.matmul(w) + b))))
+        .build(CrossEntropyLoss::new(), SGDOptimizer::default());
+    assert!(result.is_err());
+}
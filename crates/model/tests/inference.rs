@@ -0,0 +1,33 @@
+mod common;
+
+use common::*;
+use model::*;
+use ndarray::s;
+
+#[test]
+fn predict_from_many_threads_matches_one_thread() {
+    let mut nn = mlp::<f32, _>(6, 4, 8, 3, AdamOptimizer::with_lr(0.01));
+    let (x, y) = (inputs(40, 4), labels(40, 3));
+    for step in 0..5 {
+        nn.train_step(&x, &y, step, usize::MAX, "");
+    }
+    let (classes, probs) = (nn.predict(&x), nn.predict_proba(&x));
+
+    let (nn, x) = (&nn, &x);
+    let results: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                // every thread gets a different slice of rows and the whole batch
+                let rows = x.slice(s![i * 5..(i + 1) * 5, ..]).to_owned();
+                scope.spawn(move || (i, nn.predict(&rows), nn.predict(x), nn.predict_proba(x)))
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    for (i, rows, all, all_probs) in results {
+        assert_eq!(rows.to_vec(), classes.to_vec()[i * 5..(i + 1) * 5]);
+        assert_eq!(all, classes);
+        assert_eq!(all_probs, probs);
+    }
+}
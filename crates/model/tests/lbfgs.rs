@@ -0,0 +1,48 @@
+mod common;
+
+use common::*;
+use model::*;
+use ndarray::Array1;
+
+fn model() -> NN<CrossEntropyLoss, LBFGSOptimizer> {
+    mlp(6, 4, 6, 3, LBFGSOptimizer::default())
+}
+
+#[test]
+fn closure_step_reduces_loss() {
+    let (x, y) = (inputs(24, 4), labels(24, 3));
+    let mut nn = model();
+    let preds = nn.forward_all(&x);
+    let before = nn.loss_fn.forward(&preds, &y);
+    let (after, _) = nn.train_step_closure(&x, &y);
+    assert!(after < before, "{} -> {}", before, after);
+}
+
+#[test]
+fn summary_counts_history() {
+    let nn = model();
+    let summary = nn.summary(24);
+    // (s, y) pairs of 10 steps
+    assert_eq!(summary.optimizer_bytes, summary.trainable_params * 20 * 4);
+}
+
+#[test]
+fn summary_counts_history_of_trainable_params_only() {
+    let mut nn = model();
+    nn.freeze(0);
+    let summary = nn.summary(24);
+    assert_eq!(summary.trainable_params, 6 * 3 + 3);
+    assert_eq!(summary.optimizer_bytes, summary.trainable_params * 20 * 4);
+}
+
+#[test]
+fn failed_line_search_keeps_params() {
+    // any move away from 1 raises the loss while the gradient points downhill
+    let mut closure = |p: &Array1<f32>| (1.0 + (p[0] - 1.0).abs(), Array1::from_elem(1, 1.0));
+    let mut optim = LBFGSOptimizer { max_line_search: 3, ..Default::default() };
+    let mut params = Array1::from_elem(1, 1.0);
+    let loss = optim.step_closure(&mut params, &mut closure);
+    assert_eq!(params[0], 1.0);
+    assert_eq!(loss, 1.0);
+    assert!(optim.history.is_empty());
+}
@@ -0,0 +1,106 @@
+use model::*;
+use ndarray::{array, Array2};
+
+// class 2 is never a target and never predicted, class 1 is never predicted
+fn matrix() -> Array2<usize> {
+    array![[2, 0, 0], [1, 0, 0], [0, 0, 0]]
+}
+
+#[test]
+fn class_metrics_from_confusion_matrix() {
+    let cm = array![[3, 1], [2, 4]];
+    let metrics = class_metrics(&cm, ZeroDivision::Zero);
+    assert_eq!(metrics[0], ClassMetrics { precision: 0.6, recall: 0.75, f1: 6.0 / 9.0, support: 4 });
+    assert_eq!(metrics[1], ClassMetrics { precision: 0.8, recall: 4.0 / 6.0, f1: 8.0 / 11.0, support: 6 });
+}
+
+#[test]
+fn zero_division_sets_undefined_values() {
+    for (policy, value) in [(ZeroDivision::Zero, 0.0), (ZeroDivision::One, 1.0)] {
+        let metrics = class_metrics(&matrix(), policy);
+        assert_eq!(metrics[1].precision, value);
+        assert_eq!(metrics[1].recall, 0.0);
+        assert_eq!(metrics[2].precision, value);
+        assert_eq!(metrics[2].recall, value);
+        assert_eq!(metrics[2].f1, value);
+    }
+    let metrics = class_metrics(&matrix(), ZeroDivision::NaN);
+    assert!(metrics[1].precision.is_nan());
+    assert!(metrics[2].f1.is_nan());
+}
+
+#[test]
+fn nan_classes_are_left_out_of_averages() {
+    let report = EvaluationReport::new(0.5, 200.0 / 3.0, matrix(), ZeroDivision::NaN);
+    // precision only defined for class 0, recall for classes 0 and 1
+    assert_eq!(report.macro_avg.precision, 2.0 / 3.0);
+    assert_eq!(report.macro_avg.recall, 0.5);
+    // weighted by support 2 and 1
+    assert_eq!(report.weighted_avg.recall, 2.0 / 3.0);
+    assert_eq!(report.micro_avg.f1, 2.0 / 3.0);
+    assert_eq!(report.macro_avg.support, 3);
+}
+
+#[test]
+fn averages_of_zero_division_policy() {
+    let report = EvaluationReport::new(0.5, 200.0 / 3.0, matrix(), ZeroDivision::One);
+    assert_eq!(report.macro_avg.precision, (2.0 / 3.0 + 1.0 + 1.0) / 3.0);
+    assert_eq!(report.macro_avg.recall, (1.0 + 0.0 + 1.0) / 3.0);
+}
+
+#[test]
+fn empty_report_has_no_nan() {
+    let report = EvaluationReport::new(0.0, 0.0, Array2::zeros((2, 2)), ZeroDivision::Zero);
+    for m in report.per_class.iter().chain([&report.macro_avg, &report.micro_avg, &report.weighted_avg]) {
+        assert_eq!((m.precision, m.recall, m.f1), (0.0, 0.0, 0.0));
+    }
+}
+
+#[test]
+fn report_serializes_to_json() {
+    let report = EvaluationReport::new(0.25, 75.0, array![[3, 1], [0, 0]], ZeroDivision::Zero);
+    let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+    assert_eq!(json["confusion_matrix"], serde_json::json!([[3, 1], [0, 0]]));
+    assert_eq!(json["accuracy"], 75.0);
+    assert_eq!(json["per_class"][1]["support"], 0);
+    assert_eq!(json["zero_division"], "Zero");
+    assert!(json.get("probability_metrics").is_none());
+}
+
+#[test]
+fn confusion_matrix_rejects_out_of_range_labels() {
+    let cm = confusion_matrix(&array![0, 1, 1], &array![0, 1, 0], 2).unwrap();
+    assert_eq!(cm, array![[1, 0], [1, 1]]);
+    assert!(confusion_matrix(&array![0, 2], &array![0, 1], 2).is_err());
+    assert!(confusion_matrix(&array![0], &array![0, 1], 2).is_err());
+}
+
+// predicts class 1 for every row
+fn constant_model() -> NN<CrossEntropyLoss, SGDOptimizer> {
+    let mut nn = NNBuilder::new(2)
+        .dense(2, Initialization::Constant(0.0), Regularization::None)
+        .build(CrossEntropyLoss::new(), SGDOptimizer::default())
+        .unwrap();
+    nn.layers[0].params_grads().unwrap().2[1] = 1.0;
+    nn
+}
+
+#[test]
+fn test_step_with_takes_zero_division_and_bins() {
+    let (x, y) = (Array2::zeros((4, 2)), Array2::zeros((4, 1)));
+    let mut nn = constant_model();
+
+    let report = nn.test_step(&x, &y).unwrap();
+    // class 0 is never predicted
+    assert_eq!(report.per_class[0].precision, 0.0);
+    assert_eq!(report.probability_metrics.unwrap().calibration.bins.len(), 10);
+
+    let report = nn.test_step_with(&x, &y, ZeroDivision::One, 4).unwrap();
+    assert_eq!(report.zero_division, ZeroDivision::One);
+    assert_eq!(report.per_class[0].precision, 1.0);
+    assert_eq!(report.per_class[1].recall, 1.0);
+    assert_eq!(report.accuracy, 0.0);
+    assert_eq!(report.probability_metrics.unwrap().calibration.bins.len(), 4);
+
+    assert!(nn.test_step_with(&x, &y, ZeroDivision::Zero, 0).is_err());
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::*;
+use model::*;
+use ndarray::{Array1, Array2};
+
+fn model() -> NN<CrossEntropyLoss, AdamOptimizer> {
+    set_seed(9);
+    let tanh = AutogradLayer::new(
+        12,
+        Array2::from_shape_fn((12, 8), |(i, j)| ((i * 8 + j) as f32).cos() * 0.3),
+        Array1::zeros(8),
+        Arc::new(|x, w, b| (x.matmul(w) + b).tanh()),
+    )
+    .with_regularization(Regularization::L2 { lambda: 0.01 });
+    let mut nn = NNBuilder::new(6)
+        .dense(10, Initialization::He, Regularization::L1 { lambda: 0.001 })
+        .layer(LayerTypes::LeakyReLu(LeakyReLu::new(0.01)))
+        .dense(12, Initialization::He, Regularization::ElasticNet { l1: 0.001, l2: 0.01 })
+        .layer(LayerTypes::ELU(ELU::new(1.0)))
+        .layer(LayerTypes::Autograd(tanh))
+        .dense(3, Initialization::Glorot, Regularization::None)
+        .build(CrossEntropyLoss::new(), AdamOptimizer::with_lr(0.01))
+        .unwrap();
+    nn.freeze(0);
+    nn
+}
+
+#[test]
+fn parallel_training_matches_single_threaded() {
+    // 37 rows do not split evenly over 4 workers
+    let (x, y) = (inputs(37, 6), labels(37, 3));
+    let (mut serial, mut parallel) = (model(), model());
+    for step in 0..10 {
+        let (serial_loss, serial_accuracy) = serial.train_step(&x, &y, step, usize::MAX, "");
+        let (parallel_loss, parallel_accuracy) = parallel.train_step_parallel(&x, &y, 4).unwrap();
+        assert!((serial_loss - parallel_loss).abs() < 1e-5, "{} vs {}", serial_loss, parallel_loss);
+        assert_eq!(serial_accuracy, parallel_accuracy);
+    }
+    let difference = flatten_params(&mut serial.layers) - flatten_params(&mut parallel.layers);
+    assert!(difference.iter().all(|d| d.abs() < 1e-4), "{}", difference);
+}
+
+#[test]
+fn one_worker_is_the_single_threaded_step() {
+    let (x, y) = (inputs(16, 6), labels(16, 3));
+    let (mut serial, mut parallel) = (model(), model());
+    for step in 0..3 {
+        assert_eq!(serial.train_step(&x, &y, step, usize::MAX, ""), parallel.train_step_parallel(&x, &y, 1).unwrap());
+    }
+    assert_eq!(flatten_params(&mut serial.layers), flatten_params(&mut parallel.layers));
+}
+
+#[test]
+fn more_workers_than_rows() {
+    let (x, y) = (inputs(3, 6), labels(3, 3));
+    let (mut serial, mut parallel) = (model(), model());
+    let (serial_loss, _) = serial.train_step(&x, &y, 0, usize::MAX, "");
+    let (parallel_loss, _) = parallel.train_step_parallel(&x, &y, 8).unwrap();
+    assert!((serial_loss - parallel_loss).abs() < 1e-5);
+    let difference = flatten_params(&mut serial.layers) - flatten_params(&mut parallel.layers);
+    assert!(difference.iter().all(|d| d.abs() < 1e-5));
+}
+
+#[test]
+fn empty_or_mismatched_batches_are_errors() {
+    let mut nn = model();
+    let before = flatten_params(&mut nn.layers);
+    assert!(nn.train_step_parallel(&Array2::zeros((0, 6)), &Array2::zeros((0, 1)), 4).is_err());
+    assert!(nn.train_step_parallel(&inputs(4, 6), &labels(3, 3), 2).is_err());
+    assert_eq!(flatten_params(&mut nn.layers), before);
+}
+
+#[test]
+fn zero_workers_run_on_one_thread() {
+    let (x, y) = (inputs(5, 6), labels(5, 3));
+    let (mut one, mut zero) = (model(), model());
+    assert_eq!(one.train_step_parallel(&x, &y, 1).unwrap(), zero.train_step_parallel(&x, &y, 0).unwrap());
+}
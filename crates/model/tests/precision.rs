@@ -0,0 +1,181 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::*;
+use model::*;
+use ndarray::{Array1, Array2};
+
+fn trained() -> NN<CrossEntropyLoss, AdamOptimizer> {
+    let mut nn = mlp(5, 6, 16, 3, AdamOptimizer::with_lr(0.01));
+    let (x, y) = (inputs(32, 6), labels(32, 3));
+    for _ in 0..50 {
+        nn.train_step(&x, &y, 0, usize::MAX, "");
+    }
+    nn
+}
+
+fn max_difference<F: Float>(a: &Array2<f32>, b: &Array2<F>) -> f32 {
+    a.iter().zip(b.iter()).map(|(a, b)| (a - b.into_f32()).abs()).fold(0.0, f32::max)
+}
+
+#[test]
+fn f64_gradients_are_exact_to_many_digits() {
+    set_seed(1);
+    let mut nn = NNBuilder::<f64>::new(5)
+        .dense(6, Initialization::He, Regularization::ElasticNet { l1: 0.01, l2: 0.01 })
+        .layer(LayerTypes::SELU(SELU::new(1.6733, 1.0507)))
+        .dense(3, Initialization::Glorot, Regularization::None)
+        .build(MSELoss::new(), SGDOptimizer::default())
+        .unwrap();
+    let check = gradcheck_model(&mut nn, &inputs(4, 5).mapv(f64::from), &labels(4, 3).mapv(f64::from), 1e-5);
+    assert!(check.max_error() < 1e-6, "{:?}", check);
+}
+
+#[test]
+fn f64_training_reduces_loss() {
+    let mut nn = mlp(2, 6, 8, 3, NadamOptimizer::with_lr(0.01));
+    let (x, y) = (inputs(32, 6).mapv(f64::from), labels(32, 3).mapv(f64::from));
+    let (first, _) = nn.train_step(&x, &y, 0, usize::MAX, "");
+    let mut last = first;
+    for _ in 0..50 {
+        last = nn.train_step(&x, &y, 0, usize::MAX, "").0;
+    }
+    assert!(last < first, "{} -> {}", first, last);
+}
+
+#[test]
+fn half_precision_inference_follows_f32() {
+    let nn = trained();
+    let x = inputs(32, 6);
+    let probs = nn.predict_proba(&x);
+
+    let nn_f16 = nn.cast::<f16, _, _>(CrossEntropyLoss::new(), SGDOptimizer::default()).unwrap();
+    let probs_f16 = nn_f16.predict_proba(&x.mapv(f16::from_f32));
+    assert!(max_difference(&probs, &probs_f16) < 1e-2);
+
+    let nn_bf16 = nn.cast::<bf16, _, _>(CrossEntropyLoss::new(), SGDOptimizer::default()).unwrap();
+    let probs_bf16 = nn_bf16.predict_proba(&x.mapv(bf16::from_f32));
+    assert!(max_difference(&probs, &probs_bf16) < 5e-2);
+
+    assert_eq!(nn_f16.summary(32).param_bytes * 2, nn.summary(32).param_bytes);
+}
+
+#[test]
+fn cast_round_trip_keeps_f32_weights() {
+    let nn = trained();
+    let mut back = nn
+        .cast::<f64, _, _>(CrossEntropyLoss::new(), SGDOptimizer::default())
+        .unwrap()
+        .cast::<f32, _, _>(CrossEntropyLoss::new(), SGDOptimizer::default())
+        .unwrap();
+    let x = inputs(8, 6);
+    assert_eq!(nn.predict_proba(&x), back.predict_proba(&x));
+    assert_eq!(flatten_params(&mut back.layers), flatten_params(&mut trained().layers));
+}
+
+#[test]
+fn f64_weights_survive_save_and_load() {
+    let nn = trained().cast::<f64, _, _>(CrossEntropyLoss::new(), SGDOptimizer::default()).unwrap();
+    let mut weights: Vec<Array2<f64>> = Vec::new();
+    let mut bias = Vec::new();
+    for layer in nn.layers.iter() {
+        if let Ok(LayerTypes::Layer(mut layer)) = layer.cast::<f64>() {
+            let (w, _, b, _) = layer.params_grads().unwrap();
+            // values that need more than f32 precision
+            weights.push(w.mapv(|v| v + 1e-12));
+            bias.push(b.mapv(|v| v + 1e-12));
+        }
+    }
+
+    let dir = std::env::temp_dir().join(format!("nn-rs-precision-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.to_str().unwrap();
+    save_model(weights.iter_mut().collect(), bias.iter_mut().collect(), path).unwrap();
+    let (loaded_weights, loaded_bias) = load_model_weights_and_biases::<f64>(path, weights.len()).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(loaded_weights, weights);
+    assert_eq!(loaded_bias, bias);
+}
+
+fn assert_zero_gradient_step<O: Optimizer<f16>>(name: &str, mut optim: O) {
+    let mut weights = Array2::from_elem((2, 2), f16::from_f32(0.5));
+    let mut bias = Array1::from_elem(2, f16::from_f32(0.5));
+    for _ in 0..3 {
+        optim.step_weight(&mut vec![&mut weights], &mut vec![Some(&mut Array2::zeros((2, 2)))]);
+        optim.step_bias(&mut vec![&mut bias], &mut vec![Some(&mut Array1::zeros(2))]);
+    }
+    assert!(weights.iter().chain(bias.iter()).all(|&w| w == f16::from_f32(0.5)), "{}: {} {}", name, weights, bias);
+}
+
+#[test]
+fn half_precision_smoothing_stays_positive() {
+    // the 1e-8 default rounds to 0 in f16
+    assert_zero_gradient_step("Adam", AdamOptimizer::<f16>::default());
+    assert_zero_gradient_step("Nadam", NadamOptimizer::<f16>::default());
+    assert_zero_gradient_step("RMSProp", RMSPropOptimizer::<f16>::default());
+}
+
+fn autograd_tanh<F: Float>() -> AutogradLayer<F> {
+    let weights = Array2::from_shape_fn((5, 3), |(i, j)| F::from_f32(((i * 3 + j) as f32).cos() * 0.4));
+    AutogradLayer::new(5, weights, Array1::from_elem(3, F::from_f32(0.1)), Arc::new(|x, w, b| (x.matmul(w) + b).tanh()))
+}
+
+#[test]
+fn f64_autograd_layer_computes_in_f64() {
+    let check = gradcheck(&mut autograd_tanh::<f64>(), &inputs(4, 5).mapv(f64::from), 1e-5);
+    assert!(check.max_error() < 1e-6, "{:?}", check);
+}
+
+#[test]
+fn f64_autograd_loss_computes_in_f64() {
+    let preds = inputs(4, 3).mapv(f64::from);
+    let targets = preds.mapv(|v| v * 0.5 + 0.1);
+    let mut autograd = AutogradLoss::new(Arc::new(|preds, targets| (preds - targets).powi(2).mean()));
+    let loss = autograd.forward(&preds, &targets);
+    let diff = &preds - &targets;
+    let expected = diff.mapv(|d| d * d).sum() / 12.0;
+    // an f32 tape is off by about 1e-8
+    assert!((loss - expected).abs() < 1e-12, "{} vs {}", loss, expected);
+    let (grad, expected) = (autograd.backward(&preds), diff.mapv(|d| 2.0 * d / 12.0));
+    assert!(grad.iter().zip(expected.iter()).all(|(a, b)| (a - b).abs() < 1e-12), "{} vs {}", grad, expected);
+}
+
+#[test]
+fn autograd_layer_casts_between_types_computing_in_f32() {
+    let layer = autograd_tanh::<f32>();
+    let x = inputs(4, 5);
+    let half = layer.cast::<f16>().unwrap().infer(&x.mapv(f16::from_f32));
+    let difference = layer.infer(&x) - half.mapv(f16::to_f32);
+    assert!(difference.iter().all(|d| d.abs() < 1e-2), "{}", difference);
+}
+
+#[test]
+fn autograd_layer_needs_a_new_block_for_f64() {
+    let err = autograd_tanh::<f32>().cast::<f64>().err().unwrap().to_string();
+    assert!(err.contains("can't be cast to f64"), "{}", err);
+}
+
+fn autograd_model() -> NN<CrossEntropyLoss, SGDOptimizer> {
+    set_seed(8);
+    NNBuilder::new(5)
+        .layer(LayerTypes::Autograd(autograd_tanh::<f32>()))
+        .dense(3, Initialization::Glorot, Regularization::None)
+        .build(CrossEntropyLoss::new(), SGDOptimizer::default())
+        .unwrap()
+}
+
+#[test]
+fn autograd_model_casts_to_half_precision() {
+    let nn = autograd_model();
+    let x = inputs(8, 5);
+    let half = nn.cast::<f16, _, _>(CrossEntropyLoss::new(), SGDOptimizer::default()).unwrap();
+    assert!(max_difference(&nn.predict_proba(&x), &half.predict_proba(&x.mapv(f16::from_f32))) < 1e-2);
+}
+
+#[test]
+fn autograd_model_cast_to_f64_is_an_error() {
+    let err = autograd_model().cast::<f64, _, _>(CrossEntropyLoss::new(), SGDOptimizer::default()).err().unwrap().to_string();
+    assert!(err.contains("autograd layer"), "{}", err);
+}
@@ -0,0 +1,71 @@
+use model::*;
+use ndarray::{array, Array1, Array2};
+
+fn separable() -> (Array2<f32>, Array1<usize>) {
+    (array![[0.9, 0.1], [0.2, 0.8], [0.6, 0.4]], array![0, 1, 1])
+}
+
+#[test]
+fn separable_classes_have_unit_auc() {
+    let (probs, labels) = separable();
+    let metrics = ProbabilityMetrics::new(&probs, &labels, 2);
+    assert_eq!(metrics.roc_auc, vec![1.0, 1.0]);
+    assert_eq!(metrics.macro_roc_auc, Some(1.0));
+    assert_eq!(metrics.macro_average_precision, Some(1.0));
+    assert!((metrics.top_k_accuracy[0] - 200.0 / 3.0).abs() < 1e-4);
+    assert_eq!(metrics.top_k_accuracy[1], 100.0);
+}
+
+#[test]
+fn macro_averages_skip_undefined_classes() {
+    // class 2 has no targets
+    let probs = array![[0.7, 0.2, 0.1], [0.2, 0.7, 0.1]];
+    let metrics = ProbabilityMetrics::new(&probs, &array![0, 1], 2);
+    assert!(metrics.roc_auc[2].is_nan());
+    assert_eq!(metrics.macro_roc_auc, Some(1.0));
+}
+
+#[test]
+fn macro_averages_are_none_without_defined_classes() {
+    // every row is class 0, no class has both positives and negatives for a ROC curve
+    let probs = array![[0.7, 0.3], [0.4, 0.6]];
+    let metrics = ProbabilityMetrics::new(&probs, &array![0, 0], 2);
+    assert!(metrics.roc_auc.iter().all(|auc| auc.is_nan()));
+    assert_eq!(metrics.macro_roc_auc, None);
+    assert!(metrics.to_string().contains("undefined"));
+}
+
+#[test]
+fn nan_probabilities_rank_lowest() {
+    let probs = array![[f32::NAN, 0.4], [0.2, 0.8], [f32::NAN, f32::NAN]];
+    let labels = array![1, 1, 0];
+    let calibration = calibration(&probs, &labels, 2);
+    // row 0 predicts class 1 at 0.4, row 1 class 1 at 0.8, row 2 has no confidence
+    assert_eq!(calibration.bins[0].count, 2);
+    assert_eq!(calibration.bins[1].count, 1);
+    assert_eq!(calibration.bins[0].accuracy, 0.5);
+
+    let auc = roc_auc(&probs, &labels);
+    assert_eq!(auc[1], 1.0);
+}
+
+#[test]
+fn nan_logits_rank_lowest_in_argmax() {
+    let preds = array![[f32::NAN, 0.2, 0.1], [0.3, f32::NAN, 0.5]];
+    assert_eq!(accuracy(&preds, &array![[1.0], [2.0]]), 100.0);
+}
+
+#[test]
+fn diverged_model_still_predicts_and_evaluates() {
+    let mut nn = NNBuilder::<f32>::new(2)
+        .dense(3, Initialization::Constant(f32::NAN), Regularization::None)
+        .build(CrossEntropyLoss::new(), SGDOptimizer::default())
+        .unwrap();
+    let (x, y) = (Array2::ones((4, 2)), Array2::zeros((4, 1)));
+    // every probability is NaN, ties go to the last class
+    assert_eq!(nn.predict(&x), array![2, 2, 2, 2]);
+    let report = nn.test_step(&x, &y).unwrap();
+    assert_eq!(report.accuracy, 0.0);
+    let (_, accuracy) = nn.train_step(&x, &y, 0, usize::MAX, "");
+    assert_eq!(accuracy, 0.0);
+}
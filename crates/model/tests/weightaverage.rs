@@ -0,0 +1,58 @@
+use model::*;
+use ndarray::Array2;
+
+fn model() -> NN<MSELoss, SGDOptimizer> {
+    NNBuilder::new(2)
+        .dense(2, Initialization::Constant(0.0), Regularization::None)
+        .build(MSELoss::new(), SGDOptimizer::default())
+        .unwrap()
+}
+
+fn set_weights(nn: &mut NN<MSELoss, SGDOptimizer>, value: f32) {
+    let (weights, _, bias, _) = nn.layers[0].params_grads().unwrap();
+    weights.fill(value);
+    bias.fill(value);
+}
+
+// shadow weights after updates at weights 0, 1, 2, ...
+fn average(mode: Averaging, steps: usize) -> f32 {
+    let mut nn = model();
+    let mut average = WeightAverage::new(mode);
+    for step in 0..steps {
+        set_weights(&mut nn, step as f32);
+        average.update(&mut nn);
+    }
+    assert!(average.shadow_b[0].iter().all(|&b| b == average.shadow_w[0][[0, 0]]));
+    average.shadow_w[0][[0, 0]]
+}
+
+#[test]
+fn swa_averages_from_start() {
+    // mean of 2 and 3
+    assert_eq!(average(Averaging::SWA { start: 2 }, 4), 2.5);
+    // mean of 0, 1, 2 and 3
+    assert_eq!(average(Averaging::SWA { start: 0 }, 4), 1.5);
+    assert_eq!(average(Averaging::SWA { start: 1 }, 4), 2.0);
+    // not averaging yet
+    assert_eq!(average(Averaging::SWA { start: 5 }, 4), 3.0);
+}
+
+#[test]
+fn ema_follows_decay() {
+    // 0, then 0.5 * 0 + 0.5 * 1, then 0.5 * 0.5 + 0.5 * 2
+    assert_eq!(average(Averaging::EMA { decay: 0.5 }, 3), 1.25);
+}
+
+#[test]
+fn swap_exchanges_live_and_shadow_weights() {
+    let mut nn = model();
+    let mut average = WeightAverage::new(Averaging::SWA { start: 0 });
+    for value in [1.0, 3.0] {
+        set_weights(&mut nn, value);
+        average.update(&mut nn);
+    }
+    average.swap(&mut nn);
+    assert_eq!(nn.layers[0].params_grads().unwrap().0, &Array2::from_elem((2, 2), 2.0));
+    average.swap(&mut nn);
+    assert_eq!(nn.layers[0].params_grads().unwrap().0, &Array2::from_elem((2, 2), 3.0));
+}
@@ -1,19 +1,20 @@
-use ndarray::{Array1, Array2};
-use super::Optimizer;
+use ndarray::{Array1, Array2, Zip};
+use float::Float;
+use super::{Optimizer, OptimizerState};
 
-pub struct AdamOptimizer {
+pub struct AdamOptimizer<F = f32> {
     pub lr: f32,
     pub momentum: f32,
     pub decay_rate: f32,
     pub smoothing: f32,
-    pub velocity_w: Vec<Option<Array2<f32>>>,
-    pub velocity_b: Vec<Option<Array1<f32>>>,
-    pub scaling_factor_w: Vec<Option<Array2<f32>>>,
-    pub scaling_factor_b: Vec<Option<Array1<f32>>>,
+    pub velocity_w: Vec<Option<Array2<F>>>,
+    pub velocity_b: Vec<Option<Array1<F>>>,
+    pub scaling_factor_w: Vec<Option<Array2<F>>>,
+    pub scaling_factor_b: Vec<Option<Array1<F>>>,
     pub timestep: usize,
 }
 
-impl AdamOptimizer {
+impl<F: Float> AdamOptimizer<F> {
     pub fn new(lr: f32, momentum: f32, decay_rate: f32, smoothing: f32) -> Self {
         Self {
             lr,
@@ -28,58 +29,86 @@ impl AdamOptimizer {
         }
     }
 
-    fn ensure_state_w(&mut self, params: &Vec<&mut Array2<f32>>) {
+    // momentum 0.9, decay rate 0.999, smoothing 1e-8
+    pub fn with_lr(lr: f32) -> Self {
+        Self::new(lr, 0.9, 0.999, 1e-8)
+    }
+
+    fn ensure_state_w(&mut self, params: &Vec<&mut Array2<F>>) {
         if self.velocity_w.len() != params.len() {
             self.velocity_w = params.iter().map(|p| Some(Array2::zeros(p.raw_dim()))).collect();
             self.scaling_factor_w = params.iter().map(|p| Some(Array2::zeros(p.raw_dim()))).collect();
         }
     }
 
-    fn ensure_state_b(&mut self, params: &Vec<&mut Array1<f32>>) {
+    fn ensure_state_b(&mut self, params: &Vec<&mut Array1<F>>) {
         if self.velocity_b.len() != params.len() {
             self.velocity_b = params.iter().map(|p| Some(Array1::zeros(p.raw_dim()))).collect();
             self.scaling_factor_b = params.iter().map(|p| Some(Array1::zeros(p.raw_dim()))).collect();
         }
     }
+
+    // update of one value from its gradient, first and second moment
+    fn update(&self) -> impl Fn(&mut F, &F, &mut F, &mut F) + use<F> {
+        let t = self.timestep as i32;
+        // 1e-8 rounds to 0 in f16, a zero gradient would then divide 0 by 0
+        let (lr, smoothing) = (F::from_f32(self.lr), F::from_f32(self.smoothing).max(F::min_positive_value()));
+        let (momentum, decay) = (F::from_f32(self.momentum), F::from_f32(self.decay_rate));
+        let (keep_m, keep_d) = (F::from_f32(1.0 - self.momentum), F::from_f32(1.0 - self.decay_rate));
+        let correct_m = F::from_f32(1.0 - self.momentum.powi(t));
+        let correct_d = F::from_f32(1.0 - self.decay_rate.powi(t));
+
+        move |p, &g, vel, scale| {
+            *vel = momentum * *vel + keep_m * g;
+            *scale = decay * *scale + keep_d * (g * g);
+
+            let m_hat = *vel / correct_m;
+            let s_hat = *scale / correct_d;
+
+            *p -= lr * m_hat / (s_hat.sqrt() + smoothing);
+        }
+    }
+}
+
+impl<F: Float> Default for AdamOptimizer<F> {
+    fn default() -> Self {
+        Self::with_lr(0.001)
+    }
 }
 
-impl Optimizer for AdamOptimizer {
-    fn step_weight(&mut self, params: &mut Vec<&mut Array2<f32>>, grads: &mut Vec<&mut Array2<f32>>) {
+impl<F: Float> Optimizer<F> for AdamOptimizer<F> {
+    fn step_weight(&mut self, params: &mut Vec<&mut Array2<F>>, grads: &mut Vec<Option<&mut Array2<F>>>) {
         self.timestep += 1;
         self.ensure_state_w(params);
+        let update = self.update();
 
         for ((p, g), (v, s)) in params.iter_mut().zip(grads.iter())
             .zip(self.velocity_w.iter_mut().zip(self.scaling_factor_w.iter_mut())) {
+            let Some(g) = g else { continue };
             
             let vel = v.as_mut().unwrap();
             let scale = s.as_mut().unwrap();
-
-            *vel = self.momentum * &*vel + (1.0 - self.momentum) * &**g;
-            *scale = (self.decay_rate * &*scale) + (1.0 - self.decay_rate) * &g.mapv(|x| x * x);
-
-            let m_hat = &*vel / (1.0 - self.momentum.powi(self.timestep as i32));
-            let s_hat = &*scale / (1.0 - self.decay_rate.powi(self.timestep as i32));
-
-            **p -= &(self.lr * &m_hat / (s_hat.mapv(|x| x.sqrt() + self.smoothing)));
+            Zip::from(&mut **p).and(&**g).and(vel).and(scale).for_each(&update);
         }
     }
 
-    fn step_bias(&mut self, params: &mut Vec<&mut Array1<f32>>, grads: &mut Vec<&mut Array1<f32>>) {
+    fn step_bias(&mut self, params: &mut Vec<&mut Array1<F>>, grads: &mut Vec<Option<&mut Array1<F>>>) {
         self.ensure_state_b(params);
+        let update = self.update();
 
         for ((p, g), (v, s)) in params.iter_mut().zip(grads.iter())
             .zip(self.velocity_b.iter_mut().zip(self.scaling_factor_b.iter_mut())) {
+            let Some(g) = g else { continue };
 
             let vel = v.as_mut().unwrap();
             let scale = s.as_mut().unwrap();
-
-            *vel = self.momentum * &*vel + (1.0 - self.momentum) * &**g;
-            *scale = (self.decay_rate * &*scale) + (1.0 - self.decay_rate) * &g.mapv(|x| x * x);
-
-            let m_hat = &*vel / (1.0 - self.momentum.powi(self.timestep as i32));
-            let s_hat = &*scale / (1.0 - self.decay_rate.powi(self.timestep as i32));
-
-            **p -= &(self.lr * &m_hat / (s_hat.mapv(|x| x.sqrt() + self.smoothing)));
+            Zip::from(&mut **p).and(&**g).and(vel).and(scale).for_each(&update);
         }
     }
 }
+
+impl<F: Float> OptimizerState for AdamOptimizer<F> {
+    fn state_per_param(&self) -> usize {
+        2
+    }
+}
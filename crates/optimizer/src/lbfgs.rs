@@ -0,0 +1,153 @@
+use std::collections::VecDeque;
+
+use ndarray::Array1;
+use float::Float;
+use super::{ClosureOptimizer, LossClosure, OptimizerState};
+
+pub struct LBFGSOptimizer<F = f32> {
+    pub lr: f32,
+    pub max_iter: usize,
+    pub history_size: usize,
+    pub max_line_search: usize,
+    // sufficient decrease constant of the Armijo condition
+    pub c1: f32,
+    pub tolerance_grad: f32,
+    pub tolerance_change: f32,
+    // pairs of (parameter change, gradient change)
+    pub history: VecDeque<(Array1<F>, Array1<F>)>,
+}
+
+impl<F: Float> LBFGSOptimizer<F> {
+    pub fn new(lr: f32, max_iter: usize, history_size: usize) -> Self {
+        LBFGSOptimizer {
+            lr,
+            max_iter,
+            history_size,
+            max_line_search: 25,
+            c1: 1e-4,
+            tolerance_grad: 1e-7,
+            tolerance_change: 1e-9,
+            history: VecDeque::new(),
+        }
+    }
+
+    // Two-loop recursion, returns -H * grad
+    fn direction(&self, grad: &Array1<F>) -> Array1<F> {
+        let mut q = grad.clone();
+        let mut alphas = Vec::with_capacity(self.history.len());
+
+        for (s, y) in self.history.iter().rev() {
+            let rho = F::one() / y.dot(s);
+            let alpha = rho * s.dot(&q);
+            q.scaled_add(-alpha, y);
+            alphas.push((rho, alpha));
+        }
+
+        // initial hessian approximation scaled by the latest curvature pair
+        if let Some((s, y)) = self.history.back() {
+            let gamma = s.dot(y) / y.dot(y);
+            q.mapv_inplace(|x| x * gamma);
+        }
+
+        for ((s, y), (rho, alpha)) in self.history.iter().zip(alphas.into_iter().rev()) {
+            let beta = rho * y.dot(&q);
+            q.scaled_add(alpha - beta, s);
+        }
+
+        q.mapv(|x| -x)
+    }
+}
+
+// lr 1, 20 iterations, history of 10
+impl<F: Float> Default for LBFGSOptimizer<F> {
+    fn default() -> Self {
+        Self::new(1.0, 20, 10)
+    }
+}
+
+impl<F: Float> ClosureOptimizer<F> for LBFGSOptimizer<F> {
+    fn step_closure(&mut self, params: &mut Array1<F>, closure: &mut LossClosure<F>) -> F {
+        let (mut loss, mut grad) = closure(params);
+        let (lr, c1) = (F::from_f32(self.lr), F::from_f32(self.c1));
+        let tolerance_grad = F::from_f32(self.tolerance_grad);
+        let tolerance_change = F::from_f32(self.tolerance_change);
+
+        for _ in 0..self.max_iter {
+            if grad.iter().all(|g| g.abs() <= tolerance_grad) {
+                break;
+            }
+
+            let mut dir = self.direction(&grad);
+            let mut grad_dot_dir = grad.dot(&dir);
+            if grad_dot_dir >= F::zero() {
+                // not a descent direction, history is stale
+                self.history.clear();
+                dir = grad.mapv(|g| -g);
+                grad_dot_dir = grad.dot(&dir);
+            }
+
+            // first step has no curvature information, keep it small
+            let mut step = if self.history.is_empty() {
+                lr * (F::one() / grad.mapv(|g| g.abs()).sum()).min(F::one())
+            } else {
+                lr
+            };
+
+            // Backtracking line search on the Armijo condition
+            let moved = |step: F| {
+                let mut candidate = params.clone();
+                candidate.scaled_add(step, &dir);
+                candidate
+            };
+            let sufficient_decrease = |new_loss: F, step: F| new_loss <= loss + c1 * step * grad_dot_dir;
+            let mut candidate = moved(step);
+            let (mut new_loss, mut new_grad) = closure(&candidate);
+            for _ in 0..self.max_line_search {
+                if sufficient_decrease(new_loss, step) {
+                    break;
+                }
+                step /= F::from_f32(2.0);
+                candidate = moved(step);
+                (new_loss, new_grad) = closure(&candidate);
+            }
+            if !sufficient_decrease(new_loss, step) {
+                // no step decreased the loss enough, keep the params and drop the history behind the direction
+                self.history.clear();
+                // end on an evaluation at the kept params, like every accepted step
+                closure(params);
+                break;
+            }
+
+            let s = &candidate - &*params;
+            let y = &new_grad - &grad;
+            if self.history_size > 0 && s.dot(&y) > F::from_f32(1e-10) {
+                if self.history.len() == self.history_size {
+                    self.history.pop_front();
+                }
+                self.history.push_back((s, y));
+            }
+
+            let change = (loss - new_loss).abs();
+            *params = candidate;
+            loss = new_loss;
+            grad = new_grad;
+
+            if change < tolerance_change {
+                break;
+            }
+        }
+
+        loss
+    }
+}
+
+impl<F: Float> OptimizerState for LBFGSOptimizer<F> {
+    fn state_per_param(&self) -> usize {
+        // (s, y) pairs of the history
+        2 * self.history_size
+    }
+
+    fn keeps_frozen_state(&self) -> bool {
+        false
+    }
+}
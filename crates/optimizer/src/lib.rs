@@ -1,14 +1,43 @@
 use ndarray::{Array1, Array2};
+use float::Float;
 pub use sgd::SGDOptimizer;
 pub use momentum::MomentumOptimizer;
 pub use rmsprop::RMSPropOptimizer;
 pub use nag::NAGOptimizer;
 pub use adam::AdamOptimizer;
 pub use nadam::NadamOptimizer;
+pub use lookahead::Lookahead;
+pub use lbfgs::LBFGSOptimizer;
 
-pub trait Optimizer {
-    fn step_weight(&mut self, params: &mut Vec<&mut Array2<f32>>, grads: &mut Vec<&mut Array2<f32>>);
-    fn step_bias(&mut self, params: &mut Vec<&mut Array1<f32>>, grads: &mut Vec<&mut Array1<f32>>);
+// Hyperparameters are f32 for every element type `F`, state is kept in `F`
+// `params` holds every trainable layer in model order, so state keeps its position when layers are
+// frozen or unfrozen. Frozen layers have no gradient, their weights and state are left as they are.
+pub trait Optimizer<F: Float = f32>: OptimizerState {
+    fn step_weight(&mut self, params: &mut Vec<&mut Array2<F>>, grads: &mut Vec<Option<&mut Array2<F>>>);
+    fn step_bias(&mut self, params: &mut Vec<&mut Array1<F>>, grads: &mut Vec<Option<&mut Array1<F>>>);
+}
+
+// Memory kept between steps, shared by `Optimizer` and `ClosureOptimizer` for `NN::summary`
+pub trait OptimizerState {
+    // number of values kept per trainable parameter between steps, e.g. 2 for Adam's moments
+    fn state_per_param(&self) -> usize {
+        0
+    }
+
+    // whether frozen layers keep their state too, true for state kept per layer slot,
+    // false for optimizers that only see the flattened trainable parameters
+    fn keeps_frozen_state(&self) -> bool {
+        true
+    }
+}
+
+// For optimizers that re-evaluate the loss several times per step (e.g. line search).
+// `params` holds all trainable parameters flattened, `closure` returns loss and flattened gradient at given params.
+pub type LossClosure<'a, F = f32> = dyn FnMut(&Array1<F>) -> (F, Array1<F>) + 'a;
+
+pub trait ClosureOptimizer<F: Float = f32>: OptimizerState {
+    // returns loss at the updated params
+    fn step_closure(&mut self, params: &mut Array1<F>, closure: &mut LossClosure<F>) -> F;
 }
 
 pub mod sgd;
@@ -16,4 +45,6 @@ pub mod momentum;
 pub mod rmsprop;
 pub mod nag;
 pub mod adam;
-pub mod nadam;
\ No newline at end of file
+pub mod nadam;
+pub mod lookahead;
+pub mod lbfgs;
\ No newline at end of file
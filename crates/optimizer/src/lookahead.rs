@@ -0,0 +1,86 @@
+use ndarray::{Array1, Array2};
+use float::Float;
+use super::{Optimizer, OptimizerState};
+
+pub struct Lookahead<O: Optimizer<F>, F: Float = f32> {
+    pub inner: O,
+    pub k: usize,
+    pub alpha: f32,
+    pub slow_w: Vec<Array2<F>>,
+    pub slow_b: Vec<Array1<F>>,
+    pub timestep: usize,
+}
+
+impl<F: Float, O: Optimizer<F>> Lookahead<O, F> {
+    pub fn new(inner: O, k: usize, alpha: f32) -> Self {
+        Self {
+            inner,
+            k,
+            alpha,
+            slow_w: Vec::new(),
+            slow_b: Vec::new(),
+            timestep: 0,
+        }
+    }
+
+    fn ensure_slow_w(&mut self, params: &[&mut Array2<F>]) {
+        if self.slow_w.len() != params.len() {
+            self.slow_w = params.iter().map(|p| (**p).clone()).collect();
+        }
+    }
+
+    fn ensure_slow_b(&mut self, params: &[&mut Array1<F>]) {
+        if self.slow_b.len() != params.len() {
+            self.slow_b = params.iter().map(|p| (**p).clone()).collect();
+        }
+    }
+
+    fn sync_step(&self) -> bool {
+        self.k > 0 && self.timestep.is_multiple_of(self.k)
+    }
+}
+
+// k = 5, alpha = 0.5
+impl<F: Float, O: Optimizer<F> + Default> Default for Lookahead<O, F> {
+    fn default() -> Self {
+        Self::new(O::default(), 5, 0.5)
+    }
+}
+
+impl<F: Float, O: Optimizer<F>> Optimizer<F> for Lookahead<O, F> {
+    fn step_weight(&mut self, params: &mut Vec<&mut Array2<F>>, grads: &mut Vec<Option<&mut Array2<F>>>) {
+        self.timestep += 1;
+        // slow weights start from the weights before the first fast step
+        self.ensure_slow_w(params);
+        self.inner.step_weight(params, grads);
+
+        if self.sync_step() {
+            let alpha = F::from_f32(self.alpha);
+            for ((p, s), _) in params.iter_mut().zip(self.slow_w.iter_mut()).zip(grads.iter()).filter(|(_, g)| g.is_some()) {
+                // slow += alpha * (fast - slow), then reset fast to slow
+                s.zip_mut_with(p, |s, &f| *s += alpha * (f - *s));
+                p.assign(s);
+            }
+        }
+    }
+
+    fn step_bias(&mut self, params: &mut Vec<&mut Array1<F>>, grads: &mut Vec<Option<&mut Array1<F>>>) {
+        self.ensure_slow_b(params);
+        self.inner.step_bias(params, grads);
+
+        if self.sync_step() {
+            let alpha = F::from_f32(self.alpha);
+            for ((p, s), _) in params.iter_mut().zip(self.slow_b.iter_mut()).zip(grads.iter()).filter(|(_, g)| g.is_some()) {
+                s.zip_mut_with(p, |s, &f| *s += alpha * (f - *s));
+                p.assign(s);
+            }
+        }
+    }
+}
+
+impl<F: Float, O: Optimizer<F>> OptimizerState for Lookahead<O, F> {
+    fn state_per_param(&self) -> usize {
+        // slow weights
+        self.inner.state_per_param() + 1
+    }
+}
@@ -1,14 +1,15 @@
-use ndarray::{Array1, Array2};
-use super::Optimizer;
+use ndarray::{Array1, Array2, Zip};
+use float::Float;
+use super::{Optimizer, OptimizerState};
 
-pub struct MomentumOptimizer {
+pub struct MomentumOptimizer<F = f32> {
     pub lr: f32,
     pub momentum: f32,
-    pub velocity_w: Vec<Option<Array2<f32>>>,
-    pub velocity_b: Vec<Option<Array1<f32>>>,
+    pub velocity_w: Vec<Option<Array2<F>>>,
+    pub velocity_b: Vec<Option<Array1<F>>>,
 }
 
-impl MomentumOptimizer {
+impl<F: Float> MomentumOptimizer<F> {
     pub fn new(lr: f32, momentum: f32) -> Self {
         MomentumOptimizer {
             lr,
@@ -18,7 +19,12 @@ impl MomentumOptimizer {
         }
     }
 
-    fn ensure_velocity_w(&mut self, params: &Vec<&mut Array2<f32>>) {
+    // momentum 0.9
+    pub fn with_lr(lr: f32) -> Self {
+        Self::new(lr, 0.9)
+    }
+
+    fn ensure_velocity_w(&mut self, params: &Vec<&mut Array2<F>>) {
         if self.velocity_w.len() != params.len() {
             self.velocity_w = params
                 .iter()
@@ -27,7 +33,7 @@ impl MomentumOptimizer {
         }
     }
 
-    fn ensure_velocity_b(&mut self, params: &Vec<&mut Array1<f32>>) {
+    fn ensure_velocity_b(&mut self, params: &Vec<&mut Array1<F>>) {
         if self.velocity_b.len() != params.len() {
             self.velocity_b = params
                 .iter()
@@ -37,24 +43,44 @@ impl MomentumOptimizer {
     }
 }
 
-impl Optimizer for MomentumOptimizer {
-    fn step_weight(&mut self, params: &mut Vec<&mut Array2<f32>>, grads: &mut Vec<&mut Array2<f32>>) {
+impl<F: Float> Default for MomentumOptimizer<F> {
+    fn default() -> Self {
+        Self::with_lr(0.01)
+    }
+}
+
+impl<F: Float> Optimizer<F> for MomentumOptimizer<F> {
+    fn step_weight(&mut self, params: &mut Vec<&mut Array2<F>>, grads: &mut Vec<Option<&mut Array2<F>>>) {
         self.ensure_velocity_w(params);
+        let (lr, momentum) = (F::from_f32(self.lr), F::from_f32(self.momentum));
 
         for ((p, g), v) in params.iter_mut().zip(grads.iter()).zip(self.velocity_w.iter_mut()) {
+            let Some(g) = g else { continue };
             let vel = v.as_mut().unwrap();
-            *vel = self.momentum * &*vel - self.lr * &**g;
-            **p += &*vel;
+            Zip::from(&mut **p).and(&**g).and(vel).for_each(|p, &g, v| {
+                *v = momentum * *v - lr * g;
+                *p += *v;
+            });
         }
     }
 
-    fn step_bias(&mut self, params: &mut Vec<&mut Array1<f32>>, grads: &mut Vec<&mut Array1<f32>>) {
+    fn step_bias(&mut self, params: &mut Vec<&mut Array1<F>>, grads: &mut Vec<Option<&mut Array1<F>>>) {
         self.ensure_velocity_b(params);
+        let (lr, momentum) = (F::from_f32(self.lr), F::from_f32(self.momentum));
 
         for ((p, g), v) in params.iter_mut().zip(grads.iter()).zip(self.velocity_b.iter_mut()) {
+            let Some(g) = g else { continue };
             let vel = v.as_mut().unwrap();
-            *vel = self.momentum * &*vel - self.lr * &**g;
-            **p += &*vel;
+            Zip::from(&mut **p).and(&**g).and(vel).for_each(|p, &g, v| {
+                *v = momentum * *v - lr * g;
+                *p += *v;
+            });
         }
     }
 }
+
+impl<F: Float> OptimizerState for MomentumOptimizer<F> {
+    fn state_per_param(&self) -> usize {
+        1
+    }
+}
@@ -1,15 +1,16 @@
-use ndarray::{Array1, Array2};
-use super::Optimizer;
+use ndarray::{Array1, Array2, Zip};
+use float::Float;
+use super::{Optimizer, OptimizerState};
 
-pub struct RMSPropOptimizer {
+pub struct RMSPropOptimizer<F = f32> {
     pub lr: f32,
     pub decay_rate: f32,
     pub smoothing: f32,
-    pub scaling_factor_w: Vec<Option<Array2<f32>>>,
-    pub scaling_factor_b: Vec<Option<Array1<f32>>>,
+    pub scaling_factor_w: Vec<Option<Array2<F>>>,
+    pub scaling_factor_b: Vec<Option<Array1<F>>>,
 }
 
-impl RMSPropOptimizer {
+impl<F: Float> RMSPropOptimizer<F> {
     pub fn new(lr: f32, decay_rate: f32, smoothing: f32) -> Self {
         RMSPropOptimizer {
             lr,
@@ -20,7 +21,12 @@ impl RMSPropOptimizer {
         }
     }
 
-    fn ensure_scaling_factor_w(&mut self, params: &Vec<&mut Array2<f32>>) {
+    // decay rate 0.9, smoothing 1e-8
+    pub fn with_lr(lr: f32) -> Self {
+        Self::new(lr, 0.9, 1e-8)
+    }
+
+    fn ensure_scaling_factor_w(&mut self, params: &Vec<&mut Array2<F>>) {
         if self.scaling_factor_w.len() != params.len() {
             self.scaling_factor_w = params
                 .iter()
@@ -29,7 +35,7 @@ impl RMSPropOptimizer {
         }
     }
 
-    fn ensure_scaling_factor_b(&mut self, params: &Vec<&mut Array1<f32>>) {
+    fn ensure_scaling_factor_b(&mut self, params: &Vec<&mut Array1<F>>) {
         if self.scaling_factor_b.len() != params.len() {
             self.scaling_factor_b = params
                 .iter()
@@ -37,28 +43,52 @@ impl RMSPropOptimizer {
                 .collect();
         }
     }
+
+    // lr, decay rate, 1 - decay rate and smoothing as `F`, smoothing stays positive in half precision
+    fn hyperparams(&self) -> (F, F, F, F) {
+        let smoothing = F::from_f32(self.smoothing).max(F::min_positive_value());
+        (F::from_f32(self.lr), F::from_f32(self.decay_rate), F::from_f32(1.0 - self.decay_rate), smoothing)
+    }
 }
 
-impl Optimizer for RMSPropOptimizer {
-    fn step_weight(&mut self, params: &mut Vec<&mut Array2<f32>>, grads: &mut Vec<&mut Array2<f32>>) {
+impl<F: Float> Default for RMSPropOptimizer<F> {
+    fn default() -> Self {
+        Self::with_lr(0.001)
+    }
+}
+
+impl<F: Float> Optimizer<F> for RMSPropOptimizer<F> {
+    fn step_weight(&mut self, params: &mut Vec<&mut Array2<F>>, grads: &mut Vec<Option<&mut Array2<F>>>) {
         self.ensure_scaling_factor_w(params);
+        let (lr, decay, keep, smoothing) = self.hyperparams();
 
         for ((p, g), s) in params.iter_mut().zip(grads.iter()).zip(self.scaling_factor_w.iter_mut()) {
+            let Some(g) = g else { continue };
             let scale = s.as_mut().unwrap();
-            *scale = (self.decay_rate * &*scale) + (1.0 - self.decay_rate) * &g.mapv(|x| x * x);
-            let adjusted = g.mapv(|x| x) / (&*scale + self.smoothing).mapv(|x| x.sqrt());
-            **p -= &(self.lr * &adjusted);
+            Zip::from(&mut **p).and(&**g).and(scale).for_each(|p, &g, s| {
+                *s = decay * *s + keep * (g * g);
+                *p -= lr * (g / (*s + smoothing).sqrt());
+            });
         }
     }
 
-    fn step_bias(&mut self, params: &mut Vec<&mut Array1<f32>>, grads: &mut Vec<&mut Array1<f32>>) {
+    fn step_bias(&mut self, params: &mut Vec<&mut Array1<F>>, grads: &mut Vec<Option<&mut Array1<F>>>) {
         self.ensure_scaling_factor_b(params);
+        let (lr, decay, keep, smoothing) = self.hyperparams();
 
         for ((p, g), s) in params.iter_mut().zip(grads.iter()).zip(self.scaling_factor_b.iter_mut()) {
+            let Some(g) = g else { continue };
             let scale = s.as_mut().unwrap();
-            *scale = self.decay_rate * &*scale + (1.0 - self.decay_rate) * &g.mapv(|x| x * x);
-            let adjusted = g.mapv(|x| x) / (&*scale + self.smoothing).mapv(|x| x.sqrt());
-            **p -= &(self.lr * &adjusted);
+            Zip::from(&mut **p).and(&**g).and(scale).for_each(|p, &g, s| {
+                *s = decay * *s + keep * (g * g);
+                *p -= lr * (g / (*s + smoothing).sqrt());
+            });
         }
     }
-}
\ No newline at end of file
+}
+
+impl<F: Float> OptimizerState for RMSPropOptimizer<F> {
+    fn state_per_param(&self) -> usize {
+        1
+    }
+}
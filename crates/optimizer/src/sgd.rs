@@ -1,18 +1,39 @@
 use ndarray::{Array1, Array2};
-use super::Optimizer;
+use float::Float;
+use super::{Optimizer, OptimizerState};
 
+// Keeps no state, so one value works for every element type
 pub struct SGDOptimizer {
     pub lr: f32,
 }
-impl Optimizer for SGDOptimizer {
-    fn step_weight(&mut self, params: &mut Vec<&mut Array2<f32>>, grads: &mut Vec<&mut Array2<f32>>) {
+
+impl SGDOptimizer {
+    pub fn new(lr: f32) -> Self {
+        SGDOptimizer { lr }
+    }
+}
+
+impl Default for SGDOptimizer {
+    fn default() -> Self {
+        Self::new(0.01)
+    }
+}
+
+impl OptimizerState for SGDOptimizer {}
+
+impl<F: Float> Optimizer<F> for SGDOptimizer {
+    fn step_weight(&mut self, params: &mut Vec<&mut Array2<F>>, grads: &mut Vec<Option<&mut Array2<F>>>) {
+        let lr = F::from_f32(self.lr);
         for (p, g) in params.iter_mut().zip(grads.iter()) {
-            **p -= &(self.lr * &(**g));
+            let Some(g) = g else { continue };
+            p.zip_mut_with(&**g, |p, &g| *p -= lr * g);
         }
     }
-    fn step_bias(&mut self, params: &mut Vec<&mut Array1<f32>>, grads: &mut Vec<&mut Array1<f32>>) {
+    fn step_bias(&mut self, params: &mut Vec<&mut Array1<F>>, grads: &mut Vec<Option<&mut Array1<F>>>) {
+        let lr = F::from_f32(self.lr);
         for (p, g) in params.iter_mut().zip(grads.iter()) {
-            **p -= &(self.lr * &(**g));
+            let Some(g) = g else { continue };
+            p.zip_mut_with(&**g, |p, &g| *p -= lr * g);
         }
     }
-}
\ No newline at end of file
+}
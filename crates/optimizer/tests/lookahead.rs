@@ -0,0 +1,45 @@
+use ndarray::{Array1, Array2, array};
+use optimizer::*;
+
+// one SGD step with lr 1 and gradient 1 on every parameter, the second weight is frozen
+fn step(opt: &mut Lookahead<SGDOptimizer>, w: &mut Array2<f32>, frozen: &mut Array2<f32>, b: &mut Array1<f32>) {
+    let (mut gw, mut gb) = (Array2::ones(w.dim()), Array1::ones(b.len()));
+    opt.step_weight(&mut vec![w, frozen], &mut vec![Some(&mut gw), None]);
+    opt.step_bias(&mut vec![b], &mut vec![Some(&mut gb)]);
+}
+
+#[test]
+fn slow_weights_interpolate_every_k_steps() {
+    let mut opt = Lookahead::new(SGDOptimizer::new(1.0), 2, 0.5);
+    let (mut w, mut frozen, mut b) = (array![[0.0f32, 2.0]], array![[5.0f32]], array![1.0f32]);
+
+    step(&mut opt, &mut w, &mut frozen, &mut b);
+    // fast weights move on their own between syncs
+    assert_eq!(w, array![[-1.0, 1.0]]);
+    assert_eq!(opt.slow_w[0], array![[0.0, 2.0]]);
+
+    step(&mut opt, &mut w, &mut frozen, &mut b);
+    // slow = 0 + 0.5 * (-2 - 0), and the fast weights are reset to it
+    assert_eq!(opt.slow_w[0], array![[-1.0, 1.0]]);
+    assert_eq!(w, opt.slow_w[0]);
+    assert_eq!(opt.slow_b[0], array![0.0]);
+    assert_eq!(b, array![0.0]);
+
+    step(&mut opt, &mut w, &mut frozen, &mut b);
+    step(&mut opt, &mut w, &mut frozen, &mut b);
+    // slow = -1 + 0.5 * (-3 - (-1))
+    assert_eq!(w, array![[-2.0, 0.0]]);
+    assert_eq!(b, array![-1.0]);
+    assert_eq!(frozen, array![[5.0]]);
+}
+
+#[test]
+fn k_zero_never_syncs() {
+    let mut opt = Lookahead::new(SGDOptimizer::new(1.0), 0, 0.5);
+    let (mut w, mut frozen, mut b) = (array![[0.0f32]], array![[0.0f32]], array![0.0f32]);
+    for _ in 0..3 {
+        step(&mut opt, &mut w, &mut frozen, &mut b);
+    }
+    assert_eq!(w, array![[-3.0]]);
+    assert_eq!(opt.slow_w[0], array![[0.0]]);
+}